@@ -1,33 +1,202 @@
-use amethyst::core::cgmath::{Matrix4, Vector3};
-use amethyst::core::transform::{GlobalTransform};
+use std::collections::HashMap;
+
 use amethyst::assets::{Handle, Loader};
-use amethyst::ecs::prelude::{Entity};
+use amethyst::config::Config;
 use amethyst::prelude::*;
 use amethyst::animation::{
-    Animation, InterpolationFunction, Sampler, SpriteRenderChannel, SpriteRenderPrimitive,
-};
-use amethyst::renderer::{
-    Camera, Projection, ScreenDimensions, SpriteRender
+    Animation, EndControl, InterpolationFunction, Sampler, SpriteRenderChannel,
+    SpriteRenderPrimitive,
 };
+use amethyst::renderer::SpriteRender;
+
+use components::PlayerState;
+use sprite::SpriteRef;
+use sprite_sheet_registry::SpriteSheetRegistry;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum LoopMode {
+    // Plays once and goes back to the first frame
+    Once,
+    // Plays once and stays on the last frame
+    Hold,
+    Loop,
+    LoopTimes(u32)
+}
+
+impl Default for LoopMode {
+    fn default() -> LoopMode {
+        LoopMode::Loop
+    }
+}
+
+impl LoopMode {
+    pub fn end_control(&self) -> EndControl {
+        match *self {
+            LoopMode::Once => EndControl::Normal,
+            LoopMode::Hold => EndControl::Stay,
+            LoopMode::Loop => EndControl::Loop(None),
+            LoopMode::LoopTimes(times) => EndControl::Loop(Some(times))
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ClipDefinition {
+    // Name of the sprite sheet in the `SpriteSheetRegistry`
+    pub sprite_sheet: String,
+    // Sprite numbers or frame names of the sheet
+    pub frames: Vec<SpriteRef>,
+    // Seconds per frame, either one value per frame or a single value for all
+    pub durations: Vec<f32>,
+    #[serde(default)]
+    pub loop_mode: LoopMode,
+    // Play faster or slower depending on how fast the entity moves
+    #[serde(default)]
+    pub speed_scaled: bool
+}
+
+impl ClipDefinition {
+    fn duration(&self, frame: usize) -> f32 {
+        match self.durations.len() {
+            0 => 0.1,
+            1 => self.durations[0],
+            _ => self.durations.get(frame).cloned().unwrap_or(0.1)
+        }
+    }
+}
+
+/// Loaded from `resources/animations.ron`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ClipDefinitions {
+    pub clips: HashMap<String, ClipDefinition>,
+    // Which clip to play in each player state
+    #[serde(default)]
+    pub player_states: HashMap<PlayerState, String>
+}
+
+#[derive(Clone)]
+pub struct Clip {
+    // Id of the clip in the `AnimationControlSet`
+    pub id: u32,
+    pub handle: Handle<Animation<SpriteRender>>,
+    pub loop_mode: LoopMode,
+    pub speed_scaled: bool
+}
+
+/// Every loaded animation clip keyed by its name
+#[derive(Default)]
+pub struct AnimationClips {
+    clips: HashMap<String, Clip>,
+    player_states: HashMap<PlayerState, String>,
+    // First id not taken by any clip loaded so far
+    next_id: u32
+}
+
+impl AnimationClips {
+    pub fn get(&self, name: &str) -> Option<&Clip> {
+        self.clips.get(name)
+    }
+
+    pub fn player_states(&self) -> &HashMap<PlayerState, String> {
+        &self.player_states
+    }
+
+    /// Loads the clips from `path` and puts them into the world as a resource.
+    /// Sprite sheets need to be loaded into the `SpriteSheetRegistry` first.
+    /// Loading again gives the clips new ids, so a new clip can start while
+    /// the old one with the same name is still being aborted.
+    pub fn load_all(world: &mut World, path: &str) {
+        let definitions = match ClipDefinitions::load_no_fallback(path) {
+            Ok(definitions) => definitions,
+            Err(err) => {
+                println!("Failed to load animation clips `{}`: {:?}", path, err);
+                ClipDefinitions::default()
+            }
+        };
+
+        let mut names = definitions.clips.keys().cloned().collect::<Vec<String>>();
+        // Sorted so every clip gets the same id on every run
+        names.sort();
+
+        let first_id = world.res.try_fetch::<AnimationClips>().map_or(0, |old| old.next_id);
+        let mut clips = AnimationClips::default();
+        clips.next_id = first_id + names.len() as u32;
+        for (n, name) in names.into_iter().enumerate() {
+            let definition = &definitions.clips[&name];
+            let (sprite_sheet_id, frames) = match world.read_resource::<SpriteSheetRegistry>().get(&definition.sprite_sheet) {
+                Some(sheet) => (
+                    sheet.index,
+                    definition
+                        .frames
+                        .iter()
+                        .map(|frame| sheet.definition.sprite_number(frame).ok_or(frame))
+                        .collect::<Result<Vec<usize>, &SpriteRef>>()
+                ),
+                None => {
+                    println!("Animation clip `{}` uses unknown sprite sheet `{}`", name, definition.sprite_sheet);
+                    continue;
+                }
+            };
+            let frames = match frames {
+                Ok(frames) => frames,
+                Err(frame) => {
+                    println!("Animation clip `{}` uses unknown frame {} of `{}`", name, frame, definition.sprite_sheet);
+                    continue;
+                }
+            };
+
+            let clip = Clip {
+                id: first_id + n as u32,
+                handle: load_clip(world, definition, &frames, sprite_sheet_id),
+                loop_mode: definition.loop_mode,
+                speed_scaled: definition.speed_scaled
+            };
+            clips.clips.insert(name, clip);
+        }
+
+        clips.player_states = definitions.player_states;
+        world.add_resource(clips);
+    }
+}
+
+fn load_clip(
+    world: &mut World,
+    definition: &ClipDefinition,
+    // Sprite numbers of `definition.frames`
+    frames: &[usize],
+    sprite_sheet_id: u64
+) -> Handle<Animation<SpriteRender>> {
+    let mut input = Vec::with_capacity(frames.len() + 1);
+    let mut sprite_indices = Vec::with_capacity(frames.len() + 1);
+    let mut time = 0.0;
+
+    for (n, frame) in frames.iter().enumerate() {
+        input.push(time);
+        sprite_indices.push(SpriteRenderPrimitive::SpriteIndex(*frame));
+        time += definition.duration(n);
+    }
 
-pub fn blue_walker(world: &mut World, sprite_sheet_id: u64) -> Handle<Animation<SpriteRender>> {
-    let sprite_indices = (0..6)
-        .into_iter()
-        .map(|n| SpriteRenderPrimitive::SpriteIndex(n))
-        .collect::<Vec<SpriteRenderPrimitive>>();
+    // Repeat the last frame at the end so it's shown for its whole duration
+    if let Some(&last) = frames.last() {
+        input.push(time);
+        sprite_indices.push(SpriteRenderPrimitive::SpriteIndex(last));
+    }
 
     let sprite_index_sampler = {
         Sampler {
-            input: vec![0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6],
+            input,
             function: InterpolationFunction::Step,
             output: sprite_indices
         }
     };
 
     let sprite_sheet_sampler = Sampler {
-        input: vec![0.0, 2.3],
+        input: vec![0.0, time],
         function: InterpolationFunction::Step,
-        output: vec![SpriteRenderPrimitive::SpriteSheet(sprite_sheet_id)]
+        output: vec![
+            SpriteRenderPrimitive::SpriteSheet(sprite_sheet_id),
+            SpriteRenderPrimitive::SpriteSheet(sprite_sheet_id)
+        ]
     };
 
     let loader = world.write_resource::<Loader>();
@@ -53,20 +222,3 @@ pub fn blue_walker(world: &mut World, sprite_sheet_id: u64) -> Handle<Animation<
 
     loader.load_from_data(animation, (), &world.read_resource())
 }
-
-pub fn initialize_camera(world: &mut World) -> Entity {
-    let (width, height) = {
-        let dim = world.read_resource::<ScreenDimensions>();
-        (dim.width(), dim.height())
-    };
-
-    world
-        .create_entity()
-        .with(Camera::from(Projection::orthographic(
-            0.0, width, height, 0.0
-        )))
-        .with(GlobalTransform(Matrix4::from_translation(
-            Vector3::new(0.0, 0.0, 1.0).into()
-        )))
-        .build()
-}
\ No newline at end of file
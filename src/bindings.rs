@@ -0,0 +1,248 @@
+use std::mem;
+
+use amethyst::config::{Config, ConfigError};
+use amethyst::input::{Axis, Bindings, Button};
+use amethyst::renderer::{ElementState, Event, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent};
+
+use gamepad::{self, GamepadButton, GamepadConfig};
+
+/// Actions the states react to, in the order they are listed on the
+/// controls screen. Gamepad buttons for them are in `resources/gamepad.ron`.
+pub const ACTIONS: &[&str] = &[
+    "pause",
+    "cycle_input_mode",
+    "cycle_bounds_mode",
+    "quick_save",
+    "quick_load",
+    "quit",
+    "menu_up",
+    "menu_down",
+    "menu_select"
+];
+
+/// Actions pressed down since a state last took them, filled by the
+/// `ActionSystem` from every input device. Anything can `press` an action
+/// to trigger it without going through a device.
+#[derive(Default, Debug)]
+pub struct PressedActions {
+    actions: Vec<&'static str>
+}
+
+impl PressedActions {
+    pub fn press(&mut self, action: &'static str) {
+        if !self.actions.contains(&action) {
+            self.actions.push(action);
+        }
+    }
+
+    pub fn take(&mut self) -> Vec<&'static str> {
+        mem::replace(&mut self.actions, Vec::new())
+    }
+}
+
+pub fn bindings_path() -> String {
+    format!("{}/resources/input.ron", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Key or mouse button that was just pressed down in `event`
+pub fn pressed_button(event: &Event) -> Option<Button> {
+    match *event {
+        Event::WindowEvent { ref event, .. } => match *event {
+            WindowEvent::KeyboardInput {
+                input: KeyboardInput { state: ElementState::Pressed, virtual_keycode: Some(key), .. },
+                ..
+            } => Some(Button::Key(key)),
+            WindowEvent::MouseInput { state: ElementState::Pressed, button, .. } => Some(Button::Mouse(button)),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+// Menu actions are only read by the menus and the rest only during
+// gameplay, except for pause and quit that work in both
+fn used_in_gameplay(action: &str) -> bool {
+    !action.starts_with("menu_")
+}
+
+fn used_in_menus(action: &str) -> bool {
+    action.starts_with("menu_") || action == "pause" || action == "quit"
+}
+
+// Actions that can be pressed at the same time can't share a button
+fn overlap(action: &str, other: &str) -> bool {
+    (used_in_gameplay(action) && used_in_gameplay(other))
+        || (used_in_menus(action) && used_in_menus(other))
+}
+
+// Buttons the `InputFrameSystem` reads directly for walking with the
+// mouse, with what they do there
+fn reserved_for(button: &Button) -> Option<&'static str> {
+    match *button {
+        Button::Mouse(MouseButton::Left) => Some("walking to the cursor"),
+        Button::Mouse(MouseButton::Right) => Some("cancelling the path"),
+        Button::Key(VirtualKeyCode::LShift) | Button::Key(VirtualKeyCode::RShift) => Some("queueing waypoints"),
+        _ => None
+    }
+}
+
+/// The action, movement axis or mouse walking control that already uses
+/// `button` where `action` is read, if binding `button` to `action` would
+/// clash with one
+pub fn conflict(bindings: &Bindings<String, String>, action: &str, button: &Button) -> Option<String> {
+    if let Some(reserved) = reserved_for(button) {
+        return Some(reserved.to_string());
+    }
+
+    let other_action = bindings.actions().into_iter().find(|other| {
+        other != action
+            && overlap(action, other)
+            && bindings
+                .action_bindings(other.as_str())
+                .map_or(false, |bound| bound.contains(button))
+    });
+    if other_action.is_some() || !used_in_gameplay(action) {
+        return other_action;
+    }
+
+    // Movement is only read during gameplay
+    bindings.axes().into_iter().find(|axis| match bindings.axis(axis.as_str()) {
+        Some(&Axis::Emulated { ref pos, ref neg }) => pos == button || neg == button,
+        None => false
+    })
+}
+
+/// Same as `conflict` for the gamepad buttons of `resources/gamepad.ron`
+pub fn gamepad_conflict(config: &GamepadConfig, action: &str, button: GamepadButton) -> Option<String> {
+    config
+        .buttons
+        .iter()
+        .find(|&(other, bound)| other != action && *bound == button && overlap(action, other))
+        .map(|(other, _)| other.clone())
+}
+
+/// Makes `button` the only binding of `action`
+pub fn rebind(bindings: &mut Bindings<String, String>, action: &str, button: Button) {
+    let old = bindings
+        .action_bindings(action)
+        .map(|bound| bound.to_vec())
+        .unwrap_or_default();
+    for bound in old {
+        bindings.remove_action_binding(action, bound);
+    }
+    bindings.insert_action_binding(action.to_string(), button);
+}
+
+/// Buttons bound to `action` for showing on the screen, e.g. `Space`
+pub fn describe(bindings: &Bindings<String, String>, action: &str) -> String {
+    let names = bindings
+        .action_bindings(action)
+        .map(|bound| bound.iter().map(button_name).collect::<Vec<String>>())
+        .unwrap_or_default();
+
+    if names.is_empty() {
+        "-".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+pub fn button_name(button: &Button) -> String {
+    match *button {
+        Button::Key(key) => format!("{:?}", key),
+        Button::Mouse(button) => format!("Mouse {:?}", button),
+        Button::ScanCode(code) => format!("Scan code {}", code)
+    }
+}
+
+/// Writes the bindings over `resources/input.ron`
+pub fn save(bindings: &Bindings<String, String>) -> Result<(), ConfigError> {
+    Config::write(bindings, bindings_path())
+}
+
+/// Makes `button` the gamepad button of `action`
+pub fn rebind_gamepad(config: &mut GamepadConfig, action: &str, button: GamepadButton) {
+    config.buttons.insert(action.to_string(), button);
+}
+
+/// Writes the gamepad settings over `resources/gamepad.ron`
+pub fn save_gamepad(config: &GamepadConfig) -> Result<(), ConfigError> {
+    Config::write(config, gamepad::gamepad_config_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_bindings() -> Bindings<String, String> {
+        let mut bindings = Bindings::new();
+        bindings.insert_axis(
+            "vertical_movement".to_string(),
+            Axis::Emulated { pos: Button::Key(VirtualKeyCode::W), neg: Button::Key(VirtualKeyCode::S) }
+        );
+        bindings.insert_action_binding("pause".to_string(), Button::Key(VirtualKeyCode::Space));
+        bindings.insert_action_binding("cycle_input_mode".to_string(), Button::Key(VirtualKeyCode::I));
+        bindings.insert_action_binding("menu_up".to_string(), Button::Key(VirtualKeyCode::W));
+        bindings
+    }
+
+    #[test]
+    fn movement_and_other_action_keys_conflict() {
+        let bindings = default_bindings();
+
+        assert_eq!(
+            conflict(&bindings, "pause", &Button::Key(VirtualKeyCode::W)),
+            Some("vertical_movement".to_string())
+        );
+        assert_eq!(
+            conflict(&bindings, "pause", &Button::Key(VirtualKeyCode::I)),
+            Some("cycle_input_mode".to_string())
+        );
+        assert_eq!(conflict(&bindings, "pause", &Button::Key(VirtualKeyCode::P)), None);
+        // Its own key can be bound again
+        assert_eq!(conflict(&bindings, "pause", &Button::Key(VirtualKeyCode::Space)), None);
+    }
+
+    #[test]
+    fn mouse_walking_buttons_are_reserved() {
+        let bindings = default_bindings();
+
+        for button in &[
+            Button::Mouse(MouseButton::Left),
+            Button::Mouse(MouseButton::Right),
+            Button::Key(VirtualKeyCode::LShift),
+            Button::Key(VirtualKeyCode::RShift)
+        ] {
+            assert!(conflict(&bindings, "pause", button).is_some(), "{:?} was accepted", button);
+            assert!(conflict(&bindings, "menu_select", button).is_some(), "{:?} was accepted", button);
+        }
+        assert_eq!(conflict(&bindings, "pause", &Button::Mouse(MouseButton::Middle)), None);
+    }
+
+    #[test]
+    fn menu_actions_share_keys_with_gameplay() {
+        let bindings = default_bindings();
+
+        assert_eq!(conflict(&bindings, "menu_down", &Button::Key(VirtualKeyCode::S)), None);
+        assert_eq!(conflict(&bindings, "menu_down", &Button::Key(VirtualKeyCode::I)), None);
+        // Pause also works in the menus
+        assert_eq!(
+            conflict(&bindings, "menu_down", &Button::Key(VirtualKeyCode::Space)),
+            Some("pause".to_string())
+        );
+    }
+
+    #[test]
+    fn gamepad_buttons_conflict() {
+        let mut config = GamepadConfig::default();
+        rebind_gamepad(&mut config, "pause", GamepadButton::Start);
+        rebind_gamepad(&mut config, "menu_select", GamepadButton::South);
+
+        assert_eq!(
+            gamepad_conflict(&config, "cycle_input_mode", GamepadButton::Start),
+            Some("pause".to_string())
+        );
+        assert_eq!(gamepad_conflict(&config, "cycle_input_mode", GamepadButton::South), None);
+        assert_eq!(gamepad_conflict(&config, "pause", GamepadButton::Start), None);
+    }
+}
@@ -0,0 +1,142 @@
+use amethyst::core::cgmath::{Matrix4, Point3, SquareMatrix, Transform as CgTransform, Vector2, Vector3};
+use amethyst::core::transform::{GlobalTransform, Transform};
+use amethyst::ecs::prelude::Entity;
+use amethyst::prelude::*;
+use amethyst::renderer::{Camera, Projection, ScreenDimensions};
+
+use components::CameraFollow;
+
+// The `CameraFollowSystem` moves the camera and keeps the projection
+// matching the window size and zoom afterwards
+pub fn initialize_camera(world: &mut World, follow: CameraFollow) -> Entity {
+    let view = View::screen(&world.read_resource::<ScreenDimensions>());
+
+    let mut transform = Transform::default();
+    transform.translation = Vector3::new(0.0, 0.0, 1.0);
+
+    world
+        .create_entity()
+        .with(view.projection())
+        .with(transform)
+        .with(GlobalTransform::default())
+        .with(follow)
+        .build()
+}
+
+/// The part of the world a camera shows
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct View {
+    // Bottom left corner in world units, where the camera is
+    pub corner: Vector2<f32>,
+    pub size: Vector2<f32>
+}
+
+impl View {
+    /// View of a camera at the origin without zoom, for running without a camera
+    pub fn screen(dimensions: &ScreenDimensions) -> View {
+        View {
+            corner: Vector2::new(0.0, 0.0),
+            size: Vector2::new(dimensions.width(), dimensions.height())
+        }
+    }
+
+    pub fn of_camera(transform: &Transform, follow: &CameraFollow, dimensions: &ScreenDimensions) -> View {
+        View {
+            corner: transform.translation.truncate(),
+            size: Vector2::new(dimensions.width(), dimensions.height()) / follow.zoom
+        }
+    }
+
+    pub fn projection(&self) -> Camera {
+        Camera::from(Projection::orthographic(0.0, self.size.x, self.size.y, 0.0))
+    }
+}
+
+/// Converts a position in the window, like `InputHandler::mouse_position`,
+/// into world coordinates as seen through `camera` at `transform`, so
+/// panning and zooming are taken into account. Window coordinates start
+/// from the top left corner and grow downwards. The mouse position and
+/// `ScreenDimensions` are both in physical pixels, so the HiDPI factor
+/// cancels out.
+pub fn screen_to_world(
+    (x, y): (f64, f64),
+    camera: &Camera,
+    transform: &GlobalTransform,
+    dimensions: &ScreenDimensions
+) -> Vector2<f32> {
+    // Normalized device coordinates, -1.0 - 1.0 with y up
+    let device = Point3::new(
+        2.0 * x as f32 / dimensions.width() - 1.0,
+        1.0 - 2.0 * y as f32 / dimensions.height(),
+        0.0
+    );
+
+    // A projection that can't be inverted would be scaled to nothing,
+    // in which case nothing can be seen either
+    let unproject = camera.proj.invert().unwrap_or_else(Matrix4::identity);
+    let world = transform.0.transform_point(unproject.transform_point(device));
+    Vector2::new(world.x, world.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera_at(x: f32, y: f32, view_w: f32, view_h: f32) -> (Camera, GlobalTransform) {
+        let view = View {
+            corner: Vector2::new(x, y),
+            size: Vector2::new(view_w, view_h)
+        };
+        let transform = GlobalTransform(Matrix4::from_translation(Vector3::new(x, y, 1.0)));
+        (view.projection(), transform)
+    }
+
+    fn assert_close(actual: Vector2<f32>, expected: (f32, f32)) {
+        assert!(
+            (actual.x - expected.0).abs() < 0.01 && (actual.y - expected.1).abs() < 0.01,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn corners_of_an_unmoved_camera() {
+        let dimensions = ScreenDimensions::new(800, 600, 1.0);
+        let (camera, transform) = camera_at(0.0, 0.0, 800.0, 600.0);
+
+        assert_close(screen_to_world((0.0, 0.0), &camera, &transform, &dimensions), (0.0, 600.0));
+        assert_close(screen_to_world((800.0, 600.0), &camera, &transform, &dimensions), (800.0, 0.0));
+        assert_close(screen_to_world((200.0, 150.0), &camera, &transform, &dimensions), (200.0, 450.0));
+    }
+
+    #[test]
+    fn panned_camera() {
+        let dimensions = ScreenDimensions::new(800, 600, 1.0);
+        let (camera, transform) = camera_at(300.0, -100.0, 800.0, 600.0);
+
+        assert_close(screen_to_world((0.0, 600.0), &camera, &transform, &dimensions), (300.0, -100.0));
+        assert_close(screen_to_world((400.0, 300.0), &camera, &transform, &dimensions), (700.0, 200.0));
+    }
+
+    #[test]
+    fn zoomed_camera() {
+        let dimensions = ScreenDimensions::new(800, 600, 1.0);
+        // Zoom 2.0 shows half of the world a screen would
+        let (camera, transform) = camera_at(100.0, 50.0, 400.0, 300.0);
+
+        assert_close(screen_to_world((0.0, 600.0), &camera, &transform, &dimensions), (100.0, 50.0));
+        assert_close(screen_to_world((800.0, 0.0), &camera, &transform, &dimensions), (500.0, 350.0));
+        assert_close(screen_to_world((400.0, 300.0), &camera, &transform, &dimensions), (300.0, 200.0));
+    }
+
+    #[test]
+    fn hidpi_screen() {
+        // Physical pixels on a screen with twice the density
+        let dimensions = ScreenDimensions::new(1600, 1200, 2.0);
+        let (camera, transform) = camera_at(0.0, 0.0, 800.0, 600.0);
+
+        assert_close(screen_to_world((1600.0, 0.0), &camera, &transform, &dimensions), (800.0, 600.0));
+        assert_close(screen_to_world((400.0, 900.0), &camera, &transform, &dimensions), (200.0, 150.0));
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use amethyst::core::cgmath::Vector3;
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+use animation::{AnimationClips, Clip};
+use components::{Direction, PlayerState};
+
+/// Picks the animation clip of an entity from its `PlayerState`
+#[derive(Clone)]
+pub struct AnimationController {
+    pub clips: HashMap<PlayerState, Clip>,
+    // Clips named after a state clip and a direction, e.g. `walk_left`
+    pub directional_clips: HashMap<(PlayerState, Direction), Clip>,
+    // Id of the clip currently in the `AnimationControlSet`
+    pub current: Option<u32>,
+    // Position on the previous frame, for measuring the movement speed
+    pub last_position: Option<Vector3<f32>>
+}
+
+impl Component for AnimationController {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl AnimationController {
+    /// Controller using the state -> clip mapping from `resources/animations.ron`
+    pub fn from_clips(clips: &AnimationClips) -> Self {
+        let mut directional_clips = HashMap::new();
+        for (state, name) in clips.player_states() {
+            for direction in Direction::all() {
+                let clip_name = format!("{}_{}", name, direction.suffix());
+                if let Some(clip) = clips.get(&clip_name) {
+                    directional_clips.insert((state.clone(), *direction), clip.clone());
+                }
+            }
+        }
+
+        AnimationController {
+            clips: clips
+                .player_states()
+                .iter()
+                .filter_map(|(state, name)| clips.get(name).map(|clip| (state.clone(), clip.clone())))
+                .collect(),
+            directional_clips,
+            current: None,
+            last_position: None
+        }
+    }
+
+    pub fn clip_for(&self, state: &PlayerState) -> Option<&Clip> {
+        self.clips.get(state)
+    }
+
+    /// Clip made for the given direction, if the clips have one
+    pub fn directional_clip_for(&self, state: &PlayerState, direction: Direction) -> Option<&Clip> {
+        self.directional_clips.get(&(state.clone(), direction))
+    }
+}
@@ -0,0 +1,37 @@
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+/// Makes the camera entity follow the player, loaded from `resources/camera.ron`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraFollow {
+    // Size of the area around the middle of the view the player can move
+    // in without the camera moving, in world units
+    pub deadzone: (f32, f32),
+    // How fast the camera catches up with the player, bigger is faster.
+    // 0.0 snaps right to the player.
+    pub smoothing: f32,
+    // Keeps the view inside the tile map
+    pub clamp_to_world: bool,
+    // 2.0 shows everything twice as big
+    pub zoom: f32,
+    // View size the projection was built for, for noticing window
+    // resizes and zoom changes
+    #[serde(skip)]
+    pub projection_size: Option<(f32, f32)>
+}
+
+impl Default for CameraFollow {
+    fn default() -> CameraFollow {
+        CameraFollow {
+            deadzone: (128.0, 96.0),
+            smoothing: 5.0,
+            clamp_to_world: true,
+            zoom: 1.0,
+            projection_size: None
+        }
+    }
+}
+
+impl Component for CameraFollow {
+    type Storage = DenseVecStorage<Self>;
+}
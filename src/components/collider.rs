@@ -0,0 +1,28 @@
+use amethyst::core::cgmath::Vector2;
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+/// Axis aligned box around the entity's translation that can't go
+/// through solid tiles
+#[derive(Clone, Debug)]
+pub struct Collider {
+    pub half_extents: Vector2<f32>
+}
+
+impl Default for Collider {
+    // Size of one walker frame
+    fn default() -> Collider {
+        Collider::new(64.0, 64.0)
+    }
+}
+
+impl Component for Collider {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Collider {
+    pub fn new(width: f32, height: f32) -> Self {
+        Collider {
+            half_extents: Vector2::new(width / 2.0, height / 2.0)
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::f32::consts::PI;
+
+use amethyst::core::cgmath::Vector2;
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Direction {
+    Right,
+    UpRight,
+    Up,
+    UpLeft,
+    Left,
+    DownLeft,
+    Down,
+    DownRight
+}
+
+impl Direction {
+    // Counter-clockwise starting from `Right`, same order as the angles
+    const ALL: [Direction; 8] = [
+        Direction::Right,
+        Direction::UpRight,
+        Direction::Up,
+        Direction::UpLeft,
+        Direction::Left,
+        Direction::DownLeft,
+        Direction::Down,
+        Direction::DownRight
+    ];
+
+    pub fn all() -> &'static [Direction] {
+        &Direction::ALL
+    }
+
+    // Used for finding direction specific clips, e.g. `walk_up_left`
+    pub fn suffix(&self) -> &'static str {
+        use self::Direction::*;
+        match *self {
+            Right => "right",
+            UpRight => "up_right",
+            Up => "up",
+            UpLeft => "up_left",
+            Left => "left",
+            DownLeft => "down_left",
+            Down => "down",
+            DownRight => "down_right"
+        }
+    }
+
+    // Check if the direction points to the left side of the screen
+    pub fn is_left(&self) -> bool {
+        match *self {
+            Direction::UpLeft | Direction::Left | Direction::DownLeft => true,
+            _ => false
+        }
+    }
+}
+
+/// Set with `facing` in `resources/player.ron`
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum FacingMode {
+    // Right, up, left and down, whichever axis the movement is mostly along
+    FourWay,
+    EightWay
+}
+
+/// Which way an entity is looking, updated from its movement
+#[derive(Clone, Debug)]
+pub struct Facing {
+    pub direction: Direction,
+    pub mode: FacingMode
+}
+
+impl Default for Facing {
+    fn default() -> Facing {
+        Facing::new(FacingMode::EightWay)
+    }
+}
+
+impl Component for Facing {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Facing {
+    pub fn new(mode: FacingMode) -> Self {
+        Facing {
+            direction: Direction::Right,
+            mode
+        }
+    }
+
+    /// Turns towards `movement`, keeps the old direction when not moving
+    pub fn look_towards(&mut self, movement: Vector2<f32>) {
+        if movement.x == 0.0 && movement.y == 0.0 {
+            return;
+        }
+
+        let sectors = match self.mode {
+            FacingMode::FourWay => 4,
+            FacingMode::EightWay => 8
+        };
+        let sector_size = 2.0 * PI / sectors as f32;
+        // Angle in 0..2PI, counter-clockwise from the right
+        let angle = (movement.y.atan2(movement.x) + 2.0 * PI) % (2.0 * PI);
+        let sector = (angle / sector_size).round() as usize % sectors;
+
+        // Four way directions are every other one of the eight
+        let step = 8 / sectors;
+        self.direction = Direction::ALL[sector * step];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facing_after(mode: FacingMode, x: f32, y: f32) -> Direction {
+        let mut facing = Facing::new(mode);
+        facing.look_towards(Vector2::new(x, y));
+        facing.direction
+    }
+
+    #[test]
+    fn four_way_picks_the_dominant_axis() {
+        assert_eq!(facing_after(FacingMode::FourWay, 1.0, 0.4), Direction::Right);
+        assert_eq!(facing_after(FacingMode::FourWay, 0.4, 1.0), Direction::Up);
+        assert_eq!(facing_after(FacingMode::FourWay, -1.0, -0.9), Direction::Left);
+        assert_eq!(facing_after(FacingMode::FourWay, 0.9, -1.0), Direction::Down);
+    }
+
+    #[test]
+    fn eight_way_has_diagonals() {
+        assert_eq!(facing_after(FacingMode::EightWay, 1.0, 0.9), Direction::UpRight);
+        assert_eq!(facing_after(FacingMode::EightWay, -1.0, -0.9), Direction::DownLeft);
+        assert_eq!(facing_after(FacingMode::EightWay, 1.0, 0.1), Direction::Right);
+    }
+}
@@ -0,0 +1,27 @@
+use amethyst::core::cgmath::Vector3;
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+/// Smooths the movement of an entity moved in fixed timesteps by drawing
+/// it between its two latest simulated positions
+#[derive(Clone, Debug)]
+pub struct Interpolation {
+    // Simulated translation before and after the latest fixed step
+    pub previous: Vector3<f32>,
+    pub current: Vector3<f32>,
+    // Translation last put into the `Transform`, `None` before the first step
+    pub rendered: Option<Vector3<f32>>
+}
+
+impl Default for Interpolation {
+    fn default() -> Interpolation {
+        Interpolation {
+            previous: Vector3::new(0.0, 0.0, 0.0),
+            current: Vector3::new(0.0, 0.0, 0.0),
+            rendered: None
+        }
+    }
+}
+
+impl Component for Interpolation {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -1,5 +1,26 @@
+mod animation_controller;
+mod camera_follow;
+mod collider;
+mod facing;
+mod interpolation;
+mod move_target;
 mod player;
+mod screen_bounds;
+mod velocity;
+mod waypoints;
 
+pub use self::animation_controller::AnimationController;
+pub use self::camera_follow::CameraFollow;
+pub use self::collider::Collider;
+pub use self::facing::Direction;
+pub use self::facing::Facing;
+pub use self::facing::FacingMode;
+pub use self::interpolation::Interpolation;
+pub use self::move_target::MoveTarget;
 pub use self::player::PlayerComponent;
 pub use self::player::PlayerState;
-pub use self::player::InputState;
\ No newline at end of file
+pub use self::player::InputState;
+pub use self::screen_bounds::BoundsMode;
+pub use self::screen_bounds::ScreenBounds;
+pub use self::velocity::Velocity;
+pub use self::waypoints::Waypoints;
@@ -0,0 +1,30 @@
+use amethyst::core::cgmath::Vector3;
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+use steering::{Arrive, Easing};
+
+/// Where an entity is walking to, used by players and NPCs alike.
+/// Entities that also have `Waypoints` take their target from the path.
+#[derive(Clone, Debug)]
+pub struct MoveTarget {
+    pub target: Option<Vector3<f32>>,
+    pub steering: Arrive
+}
+
+impl Default for MoveTarget {
+    fn default() -> MoveTarget {
+        MoveTarget {
+            target: None,
+            steering: Arrive {
+                speed: 100.0,
+                slowing_radius: 0.0,
+                easing: Easing::Linear,
+                tolerance: 1.0
+            }
+        }
+    }
+}
+
+impl Component for MoveTarget {
+    type Storage = DenseVecStorage<Self>;
+}
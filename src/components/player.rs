@@ -1,12 +1,15 @@
+use amethyst::core::cgmath::{InnerSpace, Vector2};
 use amethyst::ecs::prelude::{Component, DenseVecStorage};
 
-#[derive(PartialEq, Clone, Debug)]
+use steering::{Arrive, Easing};
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize, Deserialize)]
 pub enum PlayerState {
     Standing,
     Moving
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum InputState {
     Mouse,
     Keyboard,
@@ -28,16 +31,32 @@ impl InputState {
 #[derive(Clone, Debug)]
 pub struct PlayerComponent {
     pub speed: f32,
+    // Speed gained per second with the keyboard or a controller, 0.0 for
+    // starting at full speed
+    pub acceleration: f32,
+    // Speed lost per second after letting go of the keyboard or a
+    // controller, 0.0 for stopping right away
+    pub friction: f32,
     pub state: PlayerState,
-    pub input_state: InputState
+    pub input_state: InputState,
+    // Distance from the mouse target that counts as arrived
+    pub arrival_tolerance: f32,
+    // Distance from the mouse target where slowing down starts, 0.0 for never
+    pub slowing_radius: f32,
+    pub arrival_easing: Easing
 }
 
 impl Default for PlayerComponent {
     fn default() -> PlayerComponent {
         PlayerComponent {
             speed: 100.0,
+            acceleration: 0.0,
+            friction: 0.0,
             state: PlayerState::Standing,
-            input_state: InputState::Mouse
+            input_state: InputState::Mouse,
+            arrival_tolerance: 1.0,
+            slowing_radius: 0.0,
+            arrival_easing: Easing::Linear
         }
     }
 }
@@ -61,4 +80,42 @@ impl PlayerComponent {
     pub fn uses_keyboard(&self) -> bool {
         self.input_state == InputState::Keyboard
     }
+
+    // Check if player is using gamepad input
+    pub fn uses_controller(&self) -> bool {
+        self.input_state == InputState::Controller
+    }
+
+    // Velocity after `delta_seconds` of heading in `direction`, which is at
+    // most 1.0 long. Speeds up with `acceleration` while there is input and
+    // slows down with `friction` without.
+    pub fn accelerate(&self, velocity: Vector2<f32>, direction: Vector2<f32>, delta_seconds: f32) -> Vector2<f32> {
+        let wanted = direction * self.speed;
+        let rate = if direction.x != 0.0 || direction.y != 0.0 {
+            self.acceleration
+        } else {
+            self.friction
+        };
+        if rate <= 0.0 {
+            return wanted;
+        }
+
+        let change = wanted - velocity;
+        let max_change = rate * delta_seconds;
+        if change.magnitude() <= max_change {
+            wanted
+        } else {
+            velocity + change.normalize_to(max_change)
+        }
+    }
+
+    // Steering towards the mouse target with the player's settings
+    pub fn arrive(&self) -> Arrive {
+        Arrive {
+            speed: self.speed,
+            slowing_radius: self.slowing_radius,
+            easing: self.arrival_easing,
+            tolerance: self.arrival_tolerance
+        }
+    }
 }
\ No newline at end of file
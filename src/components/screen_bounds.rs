@@ -0,0 +1,78 @@
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum BoundsMode {
+    // Stops at the edge of the screen
+    Clamp,
+    // Leaves on one side and comes back on the other
+    Wrap,
+    // Gets pushed back from the edge as far as it went over it
+    Bounce
+}
+
+impl BoundsMode {
+    // For cycling through the `BoundsMode` values
+    pub fn next(&self) -> Self {
+        use self::BoundsMode::*;
+        match *self {
+            Clamp => Wrap,
+            Wrap => Bounce,
+            Bounce => Clamp
+        }
+    }
+
+    /// Applies the mode to one coordinate of an entity reaching `below`
+    /// and `above` pixels to each side of it, on a view starting at `start`
+    /// and `size` pixels wide or tall. Returns the new coordinate and
+    /// whether it changed.
+    pub fn apply(&self, value: f32, (below, above): (f32, f32), (start, size): (f32, f32)) -> (f32, bool) {
+        // A sprite bigger than the view has nowhere to go
+        if below + above >= size {
+            return (value, false);
+        }
+
+        let (min, max) = match *self {
+            // Wraps once the whole sprite is out of sight
+            BoundsMode::Wrap => (start - above, start + size + below),
+            _ => (start + below, start + size - above)
+        };
+        if value >= min && value <= max {
+            return (value, false);
+        }
+
+        let bounded = match *self {
+            BoundsMode::Clamp => value.max(min).min(max),
+            BoundsMode::Wrap => {
+                let range = max - min;
+                min + ((value - min) % range + range) % range
+            }
+            BoundsMode::Bounce => {
+                let bounced = if value < min {
+                    min + (min - value)
+                } else {
+                    max - (value - max)
+                };
+                bounced.max(min).min(max)
+            }
+        };
+        (bounded, true)
+    }
+}
+
+/// Keeps the entity in the part of the world the camera shows
+#[derive(Clone, Debug)]
+pub struct ScreenBounds {
+    pub mode: BoundsMode
+}
+
+impl Default for ScreenBounds {
+    fn default() -> ScreenBounds {
+        ScreenBounds {
+            mode: BoundsMode::Clamp
+        }
+    }
+}
+
+impl Component for ScreenBounds {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -0,0 +1,31 @@
+use amethyst::core::cgmath::Vector2;
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+/// Pixels per second the entity moves, integrated into its translation
+/// by the `PhysicsSystem`
+#[derive(Clone, Debug)]
+pub struct Velocity {
+    pub linear: Vector2<f32>
+}
+
+impl Default for Velocity {
+    fn default() -> Velocity {
+        Velocity {
+            linear: Vector2::new(0.0, 0.0)
+        }
+    }
+}
+
+impl Component for Velocity {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Velocity {
+    pub fn is_moving(&self) -> bool {
+        self.linear.x != 0.0 || self.linear.y != 0.0
+    }
+
+    pub fn stop(&mut self) {
+        self.linear = Vector2::new(0.0, 0.0);
+    }
+}
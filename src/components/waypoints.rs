@@ -0,0 +1,43 @@
+use std::collections::VecDeque;
+
+use amethyst::core::cgmath::Vector3;
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+/// Mouse movement path of an entity, walked from front to back
+#[derive(Clone, Debug, Default)]
+pub struct Waypoints {
+    pub points: VecDeque<Vector3<f32>>,
+    // Left mouse button state on the previous step, for telling a new
+    // click from a held button
+    pub left_was_down: bool
+}
+
+impl Component for Waypoints {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Waypoints {
+    pub fn current(&self) -> Option<Vector3<f32>> {
+        self.points.front().cloned()
+    }
+
+    // Replaces the whole path with a single point
+    pub fn set(&mut self, point: Vector3<f32>) {
+        self.points.clear();
+        self.points.push_back(point);
+    }
+
+    pub fn push(&mut self, point: Vector3<f32>) {
+        self.points.push_back(point);
+    }
+
+    // Drops the reached point, returns `true` when there are more to go
+    pub fn advance(&mut self) -> bool {
+        self.points.pop_front();
+        !self.points.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+}
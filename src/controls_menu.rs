@@ -0,0 +1,96 @@
+use amethyst::input::{Bindings, InputHandler};
+use amethyst::prelude::*;
+use amethyst::ui::FontHandle;
+
+use bindings;
+use gamepad::GamepadConfig;
+use menu_list::{MenuList, MenuStyle};
+
+/// Every action and what it is bound to, shown in place of the pause menu
+pub struct ControlsMenu {
+    // One row per action, in the same order as `bindings::ACTIONS`
+    list: MenuList,
+    // Waiting for the key or button to bind to the selected action
+    capturing: bool,
+    // Why the last key or button pressed while capturing was not bound
+    rejected: Option<String>
+}
+
+impl ControlsMenu {
+    pub fn create(world: &mut World, font: &FontHandle) -> Self {
+        let labels = vec![String::new(); bindings::ACTIONS.len()];
+        let style = MenuStyle {
+            width: 500.0,
+            row_height: 40.0,
+            font_size: 30.0
+        };
+
+        let menu = ControlsMenu {
+            list: MenuList::create(world, font, "controls_menu", &labels, &style),
+            capturing: false,
+            rejected: None
+        };
+        menu.refresh(world);
+        menu
+    }
+
+    pub fn remove(self, world: &mut World) {
+        self.list.remove(world);
+    }
+
+    pub fn selected_action(&self) -> &'static str {
+        bindings::ACTIONS[self.list.selected()]
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capturing
+    }
+
+    pub fn set_capturing(&mut self, world: &mut World, capturing: bool) {
+        self.capturing = capturing;
+        self.rejected = None;
+        self.refresh(world);
+    }
+
+    /// Keeps capturing and shows why the pressed key or button wasn't bound
+    pub fn reject(&mut self, world: &mut World, reason: String) {
+        self.rejected = Some(reason);
+        self.refresh(world);
+    }
+
+    pub fn select_next(&mut self, world: &mut World) {
+        self.list.select_next(world);
+        self.refresh(world);
+    }
+
+    pub fn select_previous(&mut self, world: &mut World) {
+        self.list.select_previous(world);
+        self.refresh(world);
+    }
+
+    /// Updates the texts from the current keyboard and gamepad bindings
+    pub fn refresh(&self, world: &World) {
+        let input = world.read_resource::<InputHandler<String, String>>();
+        let gamepad_config = world.res.try_fetch::<GamepadConfig>();
+
+        for index in 0..bindings::ACTIONS.len() {
+            let label = self.label(&input.bindings, gamepad_config.as_ref().map(|config| &**config), index);
+            self.list.set_label(world, index, label);
+        }
+    }
+
+    fn label(&self, bindings: &Bindings<String, String>, gamepad_config: Option<&GamepadConfig>, index: usize) -> String {
+        let action = bindings::ACTIONS[index];
+        if index != self.list.selected() || !self.capturing {
+            let button = gamepad_config
+                .and_then(|config| config.buttons.get(action))
+                .map_or("-".to_string(), |button| format!("{:?}", button));
+            return format!("{}: {} / {}", action, bindings::describe(bindings, action), button);
+        }
+
+        match self.rejected {
+            Some(ref reason) => format!("{}: {}, press another", action, reason),
+            None => format!("{}: press a key or button", action)
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::env;
+use std::path::Path;
+
+use watcher::FileWatcher;
+
+// Seconds between looking at the files
+const CHECK_INTERVAL: f32 = 0.5;
+
+/// What needs to be loaded again after a file changed
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Reload {
+    // Any texture or sprite sheet definition under `textures/`
+    SpriteSheets,
+    Fonts,
+    Animations,
+    TileMap,
+    Camera,
+    Gamepad,
+    Bindings
+}
+
+/// Development mode, started with `--dev`. Watches `textures/`, `fonts/`
+/// and `resources/` so assets can be edited while the game runs.
+/// `resources/player.ron` is left to the `PlayerConfigSystem`, which
+/// reloads it in every mode.
+#[derive(Debug)]
+pub struct DevReloader {
+    watcher: FileWatcher,
+    since_check: f32
+}
+
+impl DevReloader {
+    pub fn from_args() -> Option<Self> {
+        if env::args().skip(1).any(|arg| arg == "--dev") {
+            Some(DevReloader::new())
+        } else {
+            None
+        }
+    }
+
+    pub fn new() -> Self {
+        let mut watcher = FileWatcher::new();
+        for directory in &["textures", "fonts", "resources"] {
+            watcher.watch_dir(format!("{}/{}", env!("CARGO_MANIFEST_DIR"), directory));
+        }
+
+        DevReloader {
+            watcher,
+            since_check: 0.0
+        }
+    }
+
+    /// What changed since the last call, checked at most every `CHECK_INTERVAL`
+    pub fn poll(&mut self, delta_seconds: f32) -> Vec<Reload> {
+        self.since_check += delta_seconds;
+        if self.since_check < CHECK_INTERVAL {
+            return Vec::new();
+        }
+        self.since_check = 0.0;
+
+        let mut reloads = Vec::new();
+        for path in self.watcher.changed() {
+            println!("`{:?}` changed", path);
+            if let Some(reload) = reload_for(&path) {
+                if !reloads.contains(&reload) {
+                    reloads.push(reload);
+                }
+            }
+        }
+        reloads
+    }
+}
+
+fn reload_for(path: &Path) -> Option<Reload> {
+    let directory = path.parent().and_then(|parent| parent.file_name()).and_then(|name| name.to_str());
+    let name = path.file_name().and_then(|name| name.to_str());
+
+    match (directory, name) {
+        (Some("textures"), _) => Some(Reload::SpriteSheets),
+        (Some("fonts"), _) => Some(Reload::Fonts),
+        (Some("resources"), Some("animations.ron")) => Some(Reload::Animations),
+        (Some("resources"), Some("map.ron")) => Some(Reload::TileMap),
+        (Some("resources"), Some("camera.ron")) => Some(Reload::Camera),
+        (Some("resources"), Some("gamepad.ron")) => Some(Reload::Gamepad),
+        (Some("resources"), Some("input.ron")) => Some(Reload::Bindings),
+        _ => None
+    }
+}
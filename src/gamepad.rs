@@ -0,0 +1,385 @@
+use std::collections::{HashMap, VecDeque};
+
+use amethyst::core::cgmath::{InnerSpace, Vector2};
+
+pub type GamepadId = usize;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY
+}
+
+// Only the gilrs source and the tests create these
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+#[derive(PartialEq, Clone, Debug)]
+pub enum GamepadEvent {
+    Connected(GamepadId),
+    Disconnected(GamepadId),
+    ButtonPressed(GamepadId, GamepadButton),
+    ButtonReleased(GamepadId, GamepadButton),
+    AxisChanged(GamepadId, GamepadAxis, f32)
+}
+
+/// Anything that can produce gamepad events, a real controller library
+/// or a scripted list of events.
+pub trait GamepadEventSource: Send {
+    /// Push every event that happened since the last call into `events`
+    fn poll(&mut self, events: &mut Vec<GamepadEvent>);
+}
+
+/// Event source that only returns what has been pushed into it.
+/// Lets the gamepad code run without any hardware attached.
+#[derive(Default, Debug)]
+pub struct SimulatedGamepad {
+    queue: VecDeque<GamepadEvent>
+}
+
+impl SimulatedGamepad {
+    pub fn new() -> Self {
+        SimulatedGamepad::default()
+    }
+
+    // Only the tests script events, the game itself never does
+    #[cfg(test)]
+    pub fn push(&mut self, event: GamepadEvent) {
+        self.queue.push_back(event);
+    }
+}
+
+impl GamepadEventSource for SimulatedGamepad {
+    fn poll(&mut self, events: &mut Vec<GamepadEvent>) {
+        events.extend(self.queue.drain(..));
+    }
+}
+
+#[cfg(feature = "gamepad")]
+pub use self::gilrs_source::GilrsSource;
+
+#[cfg(feature = "gamepad")]
+mod gilrs_source {
+    use gilrs::{self, Gilrs};
+
+    use super::{GamepadAxis, GamepadButton, GamepadEvent, GamepadEventSource};
+
+    pub struct GilrsSource {
+        gilrs: Gilrs
+    }
+
+    impl GilrsSource {
+        pub fn new() -> Result<Self, gilrs::Error> {
+            Ok(GilrsSource { gilrs: Gilrs::new()? })
+        }
+    }
+
+    impl GamepadEventSource for GilrsSource {
+        fn poll(&mut self, events: &mut Vec<GamepadEvent>) {
+            use gilrs::EventType::*;
+
+            while let Some(gilrs::Event { id, event, .. }) = self.gilrs.next_event() {
+                let event = match event {
+                    Connected => GamepadEvent::Connected(id),
+                    Disconnected => GamepadEvent::Disconnected(id),
+                    ButtonPressed(button, _) => match map_button(button) {
+                        Some(button) => GamepadEvent::ButtonPressed(id, button),
+                        None => continue
+                    },
+                    ButtonReleased(button, _) => match map_button(button) {
+                        Some(button) => GamepadEvent::ButtonReleased(id, button),
+                        None => continue
+                    },
+                    AxisChanged(axis, value, _) => match map_axis(axis) {
+                        Some(axis) => GamepadEvent::AxisChanged(id, axis, value),
+                        None => continue
+                    },
+                    _ => continue
+                };
+                events.push(event);
+            }
+        }
+    }
+
+    fn map_button(button: gilrs::Button) -> Option<GamepadButton> {
+        use gilrs::Button::*;
+        match button {
+            South => Some(GamepadButton::South),
+            East => Some(GamepadButton::East),
+            North => Some(GamepadButton::North),
+            West => Some(GamepadButton::West),
+            LeftTrigger => Some(GamepadButton::LeftTrigger),
+            RightTrigger => Some(GamepadButton::RightTrigger),
+            Select => Some(GamepadButton::Select),
+            Start => Some(GamepadButton::Start),
+            DPadUp => Some(GamepadButton::DPadUp),
+            DPadDown => Some(GamepadButton::DPadDown),
+            DPadLeft => Some(GamepadButton::DPadLeft),
+            DPadRight => Some(GamepadButton::DPadRight),
+            _ => None
+        }
+    }
+
+    fn map_axis(axis: gilrs::Axis) -> Option<GamepadAxis> {
+        use gilrs::Axis::*;
+        match axis {
+            LeftStickX => Some(GamepadAxis::LeftStickX),
+            LeftStickY => Some(GamepadAxis::LeftStickY),
+            RightStickX => Some(GamepadAxis::RightStickX),
+            RightStickY => Some(GamepadAxis::RightStickY),
+            _ => None
+        }
+    }
+}
+
+pub fn gamepad_config_path() -> String {
+    format!("{}/resources/gamepad.ron", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Gamepad settings, loaded from `resources/gamepad.ron`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamepadConfig {
+    pub horizontal_axis: GamepadAxis,
+    pub vertical_axis: GamepadAxis,
+    // Stick deflection (0.0 - 1.0) that is still treated as no input
+    pub dead_zone: f32,
+    // Exponent applied to the stick deflection after the dead zone,
+    // 1.0 is linear and bigger values give finer control near the center
+    pub response_curve: f32,
+    // Action name -> button
+    pub buttons: HashMap<String, GamepadButton>
+}
+
+impl Default for GamepadConfig {
+    fn default() -> GamepadConfig {
+        GamepadConfig {
+            horizontal_axis: GamepadAxis::LeftStickX,
+            vertical_axis: GamepadAxis::LeftStickY,
+            dead_zone: 0.2,
+            response_curve: 2.0,
+            buttons: HashMap::new()
+        }
+    }
+}
+
+impl GamepadConfig {
+    /// Applies the dead zone and the response curve to raw stick values.
+    /// The returned vector is never longer than 1.0.
+    pub fn shape_stick(&self, raw: Vector2<f32>) -> Vector2<f32> {
+        let magnitude = raw.magnitude();
+        if magnitude <= self.dead_zone || magnitude == 0.0 {
+            return Vector2::new(0.0, 0.0);
+        }
+
+        // Rescale so the output starts from zero at the edge of the dead zone
+        let scaled = ((magnitude - self.dead_zone) / (1.0 - self.dead_zone)).min(1.0);
+        raw / magnitude * scaled.powf(self.response_curve)
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct GamepadPad {
+    pub axes: HashMap<GamepadAxis, f32>,
+    pub buttons: HashMap<GamepadButton, bool>
+}
+
+impl GamepadPad {
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes.get(&axis).cloned().unwrap_or(0.0)
+    }
+
+    pub fn button_is_down(&self, button: GamepadButton) -> bool {
+        self.buttons.get(&button).cloned().unwrap_or(false)
+    }
+
+    /// Every button held down on the pad
+    pub fn pressed_buttons(&self) -> Vec<GamepadButton> {
+        self.buttons
+            .iter()
+            .filter(|&(_, &down)| down)
+            .map(|(button, _)| *button)
+            .collect()
+    }
+}
+
+/// Current state of every connected gamepad
+#[derive(Default, Debug)]
+pub struct GamepadState {
+    pads: HashMap<GamepadId, GamepadPad>,
+    // The pad that controls the player, the first one plugged in
+    active: Option<GamepadId>
+}
+
+impl GamepadState {
+    pub fn handle_event(&mut self, event: &GamepadEvent) {
+        match *event {
+            GamepadEvent::Connected(id) => {
+                self.pads.entry(id).or_insert_with(GamepadPad::default);
+                if self.active.is_none() {
+                    self.active = Some(id);
+                }
+                println!("Gamepad {} connected", id);
+            }
+            GamepadEvent::Disconnected(id) => {
+                self.pads.remove(&id);
+                // Hand the control over to some other pad, if there is one
+                if self.active == Some(id) {
+                    self.active = self.pads.keys().min().cloned();
+                }
+                println!("Gamepad {} disconnected", id);
+            }
+            GamepadEvent::ButtonPressed(id, button) => {
+                self.pad_mut(id).buttons.insert(button, true);
+            }
+            GamepadEvent::ButtonReleased(id, button) => {
+                self.pad_mut(id).buttons.insert(button, false);
+            }
+            GamepadEvent::AxisChanged(id, axis, value) => {
+                self.pad_mut(id).axes.insert(axis, value);
+            }
+        }
+    }
+
+    // Events from a pad we haven't seen yet count as the pad being connected
+    fn pad_mut(&mut self, id: GamepadId) -> &mut GamepadPad {
+        if !self.pads.contains_key(&id) {
+            self.handle_event(&GamepadEvent::Connected(id));
+        }
+        self.pads.get_mut(&id).unwrap()
+    }
+
+    pub fn active_pad(&self) -> Option<&GamepadPad> {
+        self.active.and_then(|id| self.pads.get(&id))
+    }
+
+    /// Movement of the active pad with the dead zone and curve applied
+    pub fn movement(&self, config: &GamepadConfig) -> Vector2<f32> {
+        match self.active_pad() {
+            Some(pad) => config.shape_stick(Vector2::new(
+                pad.axis(config.horizontal_axis),
+                pad.axis(config.vertical_axis)
+            )),
+            None => Vector2::new(0.0, 0.0)
+        }
+    }
+
+    /// Checks if the button bound to `action` is held on the active pad
+    pub fn action_is_down(&self, config: &GamepadConfig, action: &str) -> bool {
+        match (self.active_pad(), config.buttons.get(action)) {
+            (Some(pad), Some(button)) => pad.button_is_down(*button),
+            _ => false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Feeds everything pushed into a simulated pad into a new state
+    fn state_after(events: &[GamepadEvent]) -> GamepadState {
+        let mut source = SimulatedGamepad::new();
+        for event in events {
+            source.push(event.clone());
+        }
+
+        let mut polled = Vec::new();
+        source.poll(&mut polled);
+        let mut state = GamepadState::default();
+        for event in &polled {
+            state.handle_event(event);
+        }
+        state
+    }
+
+    fn config() -> GamepadConfig {
+        let mut config = GamepadConfig::default();
+        config.buttons.insert("pause".to_string(), GamepadButton::Start);
+        config
+    }
+
+    #[test]
+    fn stick_inside_the_dead_zone_is_ignored() {
+        let config = config();
+        let state = state_after(&[
+            GamepadEvent::Connected(0),
+            GamepadEvent::AxisChanged(0, GamepadAxis::LeftStickX, 0.15)
+        ]);
+        assert_eq!(state.movement(&config), Vector2::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn stick_movement_follows_the_curve() {
+        let config = config();
+        let state = state_after(&[
+            GamepadEvent::Connected(0),
+            GamepadEvent::AxisChanged(0, GamepadAxis::LeftStickX, 0.6)
+        ]);
+        // Halfway between the dead zone and the edge, squared by the curve
+        let movement = state.movement(&config);
+        assert!((movement.x - 0.25).abs() < 1e-5, "{:?}", movement);
+        assert_eq!(movement.y, 0.0);
+
+        let state = state_after(&[
+            GamepadEvent::Connected(0),
+            GamepadEvent::AxisChanged(0, GamepadAxis::LeftStickX, 1.0),
+            GamepadEvent::AxisChanged(0, GamepadAxis::LeftStickY, 1.0)
+        ]);
+        assert!(state.movement(&config).magnitude() <= 1.0);
+    }
+
+    #[test]
+    fn buttons_are_read_as_actions() {
+        let config = config();
+        let state = state_after(&[GamepadEvent::Connected(0), GamepadEvent::ButtonPressed(0, GamepadButton::Start)]);
+        assert!(state.action_is_down(&config, "pause"));
+        assert!(!state.action_is_down(&config, "quit"));
+
+        let state = state_after(&[
+            GamepadEvent::Connected(0),
+            GamepadEvent::ButtonPressed(0, GamepadButton::Start),
+            GamepadEvent::ButtonReleased(0, GamepadButton::Start)
+        ]);
+        assert!(!state.action_is_down(&config, "pause"));
+    }
+
+    #[test]
+    fn second_pad_takes_over_when_the_first_is_unplugged() {
+        let config = config();
+        let state = state_after(&[
+            GamepadEvent::Connected(0),
+            GamepadEvent::Connected(1),
+            GamepadEvent::ButtonPressed(1, GamepadButton::Start)
+        ]);
+        // The first pad plugged in has the control
+        assert!(!state.action_is_down(&config, "pause"));
+
+        let state = state_after(&[
+            GamepadEvent::Connected(0),
+            GamepadEvent::Connected(1),
+            GamepadEvent::ButtonPressed(1, GamepadButton::Start),
+            GamepadEvent::Disconnected(0)
+        ]);
+        assert!(state.action_is_down(&config, "pause"));
+
+        let state = state_after(&[GamepadEvent::Connected(0), GamepadEvent::Disconnected(0)]);
+        assert!(state.active_pad().is_none());
+    }
+}
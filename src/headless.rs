@@ -0,0 +1,62 @@
+use std::env;
+
+/// Running without a window or a renderer, started with `--headless`.
+/// Only the gameplay systems run, against a fake screen size.
+#[derive(Clone, Debug)]
+pub struct HeadlessConfig {
+    pub screen_width: u32,
+    pub screen_height: u32,
+    // Quit after this many updates, runs forever when `None`
+    pub frames: Option<u64>
+}
+
+impl Default for HeadlessConfig {
+    fn default() -> HeadlessConfig {
+        HeadlessConfig {
+            screen_width: 800,
+            screen_height: 600,
+            frames: None
+        }
+    }
+}
+
+impl HeadlessConfig {
+    /// Reads the headless options from the command line:
+    /// `--headless [--frames <count>] [--screen <width>x<height>]`
+    pub fn from_args() -> Option<Self> {
+        HeadlessConfig::parse(env::args().skip(1))
+    }
+
+    pub fn parse<I>(args: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut args = args.into_iter();
+        let mut headless = false;
+        let mut config = HeadlessConfig::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => headless = true,
+                "--frames" => {
+                    config.frames = args.next().and_then(|frames| frames.parse().ok());
+                }
+                "--screen" => {
+                    let size = args.next().unwrap_or_default();
+                    let mut size = size.split('x').map(|value| value.parse::<u32>());
+                    if let (Some(Ok(width)), Some(Ok(height))) = (size.next(), size.next()) {
+                        config.screen_width = width;
+                        config.screen_height = height;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if headless {
+            Some(config)
+        } else {
+            None
+        }
+    }
+}
@@ -1,62 +1,319 @@
 extern crate amethyst;
+#[cfg(feature = "gamepad")]
+extern crate gilrs;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
 
 mod animation;
+mod bindings;
+mod camera;
+mod controls_menu;
+mod dev_reload;
+mod gamepad;
+mod headless;
+mod menu_list;
+mod pause_menu;
+mod player_config;
+mod replay;
+mod save;
 mod sprite;
 mod sprite_sheet_loader;
+mod sprite_sheet_registry;
+mod steering;
+mod tilemap;
+mod watcher;
 
-use std::time::Duration;
+use std::path::Path;
 
 use amethyst::core::cgmath::{Point3, Transform as CgTransform, Vector3};
+use amethyst::core::timing::Time;
 use amethyst::core::transform::{GlobalTransform, Transform, TransformBundle};
-use amethyst::assets::{AssetStorage, Loader};
-use amethyst::ecs::prelude::Entity;
+use amethyst::assets::Loader;
+use amethyst::ecs::prelude::{Entity, Join};
 use amethyst::prelude::*;
-use amethyst::input::{is_close_requested, is_key_down, InputBundle};
-use amethyst::ui::{UiBundle, DrawUi, Anchor, TtfFormat, UiText, UiTransform};
-use amethyst::animation::{
-    get_animation_set, AnimationBundle, AnimationCommand, AnimationControl, ControlState,
-    EndControl
-};
+use amethyst::config::Config;
+use amethyst::input::{is_close_requested, is_key_down, Bindings, InputBundle, InputHandler};
+use amethyst::ui::{UiBundle, DrawUi, Anchor, FontHandle, TtfFormat, UiText, UiTransform};
+use amethyst::animation::{get_animation_set, AnimationBundle, AnimationControlSet};
 use amethyst::renderer::{
-    ColorMask, DisplayConfig, DrawSprite, Event, MaterialTextureSet, Pipeline,
-    RenderBundle, ScreenDimensions, SpriteRender, SpriteSheet, SpriteSheetHandle, SpriteSheetSet,
-    Stage, VirtualKeyCode, ALPHA
+    ColorMask, DisplayConfig, DrawSprite, ElementState, Event, MouseButton, Pipeline,
+    RenderBundle, ScreenDimensions, SpriteRender, SpriteSheetHandle, Stage, VirtualKeyCode,
+    WindowEvent, ALPHA
 };
 
 mod components;
 mod systems;
 mod pauser;
 
+use animation::AnimationClips;
 use components::*;
+use bindings::PressedActions;
+use controls_menu::ControlsMenu;
+use dev_reload::{DevReloader, Reload};
+use gamepad::{GamepadButton, GamepadConfig, GamepadEventSource, GamepadState, SimulatedGamepad};
+use headless::HeadlessConfig;
+use pause_menu::{MenuEntry, PauseMenu};
 use pauser::{CustomGameData, CustomGameDataBuilder};
-use sprite::SpriteSheetDefinition;
+use player_config::{PlayerConfig, PlayerSpawn};
+use replay::{PendingActions, ReplayMode, ReplayStatus};
+use save::SaveGame;
+use sprite_sheet_registry::SpriteSheetRegistry;
+use tilemap::TileMap;
 
 #[derive(Debug)]
 struct GameplayState {
-    player: Option<Entity>
+    player: Option<Entity>,
+    // Set when running without a window
+    headless: Option<HeadlessConfig>,
+    // Set when running with `--dev`
+    dev: Option<DevReloader>,
+    frame: u64
 }
 
-#[derive(Debug)]
-struct PausedState;
+struct PausedState {
+    menu: Option<PauseMenu>,
+    mouse_position: Option<(f64, f64)>
+}
+
+// Lets the player rebind the actions, opened from the pause menu. It is
+// driven by the same actions it rebinds.
+struct ControlsState {
+    menu: Option<ControlsMenu>,
+    // Set when a button was just bound, it also went through the actions
+    // but wasn't meant for the menu
+    captured: bool,
+    // Gamepad buttons already held when capturing started, only a button
+    // pressed after that gets bound
+    held: Vec<GamepadButton>
+}
 
 pub struct GameStateText {
     pub text: Entity
 }
 
+// The font used by all of the UI
+pub struct UiFont {
+    pub font: FontHandle
+}
+
+// Set by the pause menu, the gameplay restarts when it resumes
+#[derive(Default)]
+pub struct RestartRequest {
+    pub requested: bool
+}
+
+// Does nothing when headless, there is no text to change
+fn set_game_state_text(world: &mut World, text: &str) {
+    let text_entity = match world.res.try_fetch::<GameStateText>() {
+        Some(text_resource) => text_resource.text,
+        None => return
+    };
+    let mut ui_text_storage = world.write_storage::<UiText>();
+
+    if let Some(ui_text_storage) = ui_text_storage.get_mut(text_entity) {
+        ui_text_storage.text = text.to_string();
+    }
+}
+
+// The UI font, `None` when headless and nothing is drawn
+fn ui_font(world: &World) -> Option<FontHandle> {
+    world.res.try_fetch::<UiFont>().map(|ui_font| ui_font.font.clone())
+}
+
 impl GameplayState {
-    fn new() -> GameplayState {
+    fn new(headless: Option<HeadlessConfig>) -> GameplayState {
+        // Nothing is drawn without a window, so nothing to reload either
+        let dev = match headless {
+            Some(_) => None,
+            None => DevReloader::from_args()
+        };
+
         GameplayState {
-            player: None
+            player: None,
+            headless,
+            dev,
+            frame: 0
         }
     }
 
+    // Loads the changed assets again, in development mode
+    fn reload_assets(&mut self, world: &mut World, reloads: &[Reload]) {
+        for reload in reloads {
+            match *reload {
+                Reload::SpriteSheets => {
+                    SpriteSheetRegistry::load_all(world, "textures");
+                    // Clips and tiles find their sheets by index, which
+                    // changes when sheets are added or removed
+                    reload_animations(world);
+                    TileMap::load(world, &resource_path("map.ron"), true);
+                    push_out_of_walls(world);
+                }
+                Reload::Fonts => {
+                    let old_font = world.read_resource::<UiFont>().font.clone();
+                    let font = load_font(world);
+                    for ui_text in (&mut world.write_storage::<UiText>()).join() {
+                        if ui_text.font == old_font {
+                            ui_text.font = font.clone();
+                        }
+                    }
+                    world.add_resource(UiFont { font });
+                }
+                Reload::Animations => reload_animations(world),
+                Reload::TileMap => {
+                    TileMap::load(world, &resource_path("map.ron"), true);
+                    push_out_of_walls(world);
+                }
+                Reload::Camera => {
+                    let follow = CameraFollow::load(&resource_path("camera.ron"));
+                    for camera in (&mut world.write_storage::<CameraFollow>()).join() {
+                        // Keeps the projection, it is rebuilt if the zoom changed
+                        *camera = CameraFollow {
+                            projection_size: camera.projection_size,
+                            ..follow.clone()
+                        };
+                    }
+                }
+                Reload::Gamepad => {
+                    world.add_resource(GamepadConfig::load(&resource_path("gamepad.ron")));
+                }
+                Reload::Bindings => {
+                    match Bindings::<String, String>::load_no_fallback(&resource_path("input.ron")) {
+                        Ok(bindings) => world.write_resource::<InputHandler<String, String>>().bindings = bindings,
+                        Err(err) => println!("Failed to reload the bindings: {:?}", err)
+                    }
+                }
+            }
+            println!("Reloaded {:?}", reload);
+        }
+    }
+
+    // Creates the player entity without anything needed for drawing it.
+    // The spawn position in the player config wins over `transform`.
+    fn create_player(&mut self, world: &mut World, transform: Transform) -> Entity {
+        let (player, facing, spawn_position) = {
+            let config = world.read_resource::<PlayerConfig>();
+            (config.player(), config.facing(), config.spawn_position)
+        };
+
+        let mut transform = transform;
+        if let Some((x, y)) = spawn_position {
+            transform.translation.x = x;
+            transform.translation.y = y;
+        }
+        world.add_resource(PlayerSpawn { transform: Some(transform.clone()) });
+
+        let player_entity = world
+            .create_entity()
+            .with(transform)
+            .with(GlobalTransform::default())
+            .with(player)
+            .with(facing)
+            .with(Interpolation::default())
+            .with(Waypoints::default())
+            .with(MoveTarget::default())
+            .with(Collider::default())
+            .with(ScreenBounds::default())
+            .with(Velocity::default())
+            .build();
+
+        self.player = Some(player_entity);
+        player_entity
+    }
+
+    // Puts the player back where it started on the next fixed step
+    fn restart(&mut self, world: &mut World) {
+        world.write_resource::<PendingActions>().push("restart");
+    }
+
+    fn quick_save(&self, world: &World) {
+        let path = save::quick_save_path();
+        match SaveGame::capture(world, self.player.unwrap()).and_then(|save| save.write(&path)) {
+            Ok(()) => println!("Game saved to `{}`", path),
+            Err(err) => println!("Failed to save the game: {:?}", err)
+        }
+    }
+
+    // The player is moved on the next fixed step, so recordings have the
+    // loaded snapshot and don't need the quick save file
+    fn quick_load(&self, world: &mut World) {
+        let path = save::quick_save_path();
+        match SaveGame::read(&path) {
+            Ok(SaveGame { player: Some(snapshot), .. }) => {
+                world.write_resource::<PendingActions>().load(snapshot);
+                println!("Game loaded from `{}`", path);
+            }
+            Ok(_) => println!("Failed to load the game: {:?}", save::SaveError::MissingPlayer),
+            Err(err) => println!("Failed to load the game: {:?}", err)
+        }
+    }
+
+    // Continues from the last quick save, if there is one, starting in the
+    // input mode from `resources/player.ron` like a restart does
+    fn load_on_start(&self, world: &mut World) {
+        let path = save::quick_save_path();
+        if !Path::new(&path).exists() {
+            return;
+        }
+        // Before the first step, so a recording starts from here
+        match SaveGame::read(&path).and_then(|save| save.restore(world, self.player.unwrap())) {
+            Ok(()) => println!("Game loaded from `{}`", path),
+            Err(err) => println!("Failed to load the game: {:?}", err)
+        }
+
+        let input_state = world.read_resource::<PlayerConfig>().input_state.clone();
+        if let Some(player) = world.write_storage::<PlayerComponent>().get_mut(self.player.unwrap()) {
+            player.input_state = input_state;
+        }
+    }
+
+    // Reacts to the actions pressed with any input device
+    fn handle_actions<'a, 'b>(&mut self, world: &mut World, actions: &[&str]) -> Option<Trans<CustomGameData<'a, 'b>>> {
+        if actions.contains(&"pause") {
+            // Change the game state text to reflect the state change
+            set_game_state_text(world, "PAUSED");
+
+            // Switch to the `PausedState`
+            println!("Switching to Pausedstate");
+            return Some(Trans::Push(Box::new(PausedState::new())));
+        }
+
+        // Applied by the `PlayerActionSystem` on the next fixed step
+        for action in &["cycle_input_mode", "cycle_bounds_mode"] {
+            if actions.contains(action) {
+                world.write_resource::<PendingActions>().push(action);
+            }
+        }
+
+        if actions.contains(&"quick_save") {
+            self.quick_save(world);
+        }
+
+        if actions.contains(&"quick_load") {
+            self.quick_load(world);
+        }
+
+        if actions.contains(&"quit") {
+            return Some(Trans::Quit);
+        }
+        None
+    }
+
     fn toggle_player_sprite_animation(&mut self, world: &mut World) {
-        let mut animation_contorl_set_storage = world.write_storage();
         let player_entity = self.player.unwrap();
-        let animation_set = 
-            get_animation_set::<u32, SpriteRender>(&mut animation_contorl_set_storage, player_entity)
-                .unwrap();
-        animation_set.toggle(0);
+        // The clip that the `AnimationControllerSystem` is currently playing
+        let current_animation = world
+            .read_storage::<AnimationController>()
+            .get(player_entity)
+            .and_then(|controller| controller.current);
+
+        if let Some(animation_id) = current_animation {
+            let mut animation_contorl_set_storage = world.write_storage();
+            let animation_set = 
+                get_animation_set::<u32, SpriteRender>(&mut animation_contorl_set_storage, player_entity)
+                    .unwrap();
+            animation_set.toggle(animation_id);
+        }
     }
 
     fn draw_sprites_animated(
@@ -64,12 +321,13 @@ impl GameplayState {
         world: &mut World,
         common_transform: &Transform,
         sprite_sheet_handle: SpriteSheetHandle,
-        sprite_sheet_index: u64,
         sprite_count: usize,
         sprite_w: f32,
         sprite_h: f32
     ) {
-        let blue_walker_animation = animation::blue_walker(world, sprite_sheet_index);
+        let animation_controller = AnimationController::from_clips(
+            &world.read_resource::<AnimationClips>()
+        );
 
         let mut sprite_transform = Transform::default();
         sprite_transform.translation = Vector3::new(sprite_w, sprite_h * 2.5, 0.0);
@@ -85,33 +343,24 @@ impl GameplayState {
 
         println!("sprite_render: `{:?}`", sprite_render);
 
-        let player_entity = world
-            .create_entity()
-            .with(sprite_render)
-            .with(sprite_transform)
-            .with(GlobalTransform::default())
-            .with(components::PlayerComponent::default())
-            .build();
+        let player_entity = self.create_player(world, sprite_transform);
+        world
+            .write_storage::<SpriteRender>()
+            .insert(player_entity, sprite_render)
+            .expect("Failed to add sprite to the player entity");
+        world
+            .write_storage::<AnimationController>()
+            .insert(player_entity, animation_controller)
+            .expect("Failed to add animation controller to the player entity");
+        world
+            .write_storage::<Collider>()
+            .insert(player_entity, Collider::new(sprite_w, sprite_h))
+            .expect("Failed to add collider to the player entity");
 
-        let animation = blue_walker_animation.clone();
+        // Empty for now, the `AnimationControllerSystem` fills it
+        // with the clip matching the player state
         let mut animation_contorl_set_storage = world.write_storage();
-        let animation_set = 
-            get_animation_set::<u32, SpriteRender>(&mut animation_contorl_set_storage, player_entity)
-                .unwrap();
-
-        let animation_id = 0;
-
-        let animation_contorl = AnimationControl::new(
-            animation,
-            EndControl::Loop(None),
-            ControlState::Deferred(Duration::from_millis(200)),
-            AnimationCommand::Start,
-            1.0
-        );
-
-        animation_set.insert(animation_id, animation_contorl);
-
-        self.player = Some(player_entity);
+        get_animation_set::<u32, SpriteRender>(&mut animation_contorl_set_storage, player_entity);
     }
 }
 
@@ -121,12 +370,56 @@ impl<'a, 'b> State<CustomGameData<'a, 'b>> for GameplayState {
         let StateData { world, .. } = data;
 
         world.register::<components::PlayerComponent>();
+        world.register::<components::AnimationController>();
+        world.register::<components::Facing>();
+        world.register::<components::Interpolation>();
+        world.register::<components::Waypoints>();
+        world.register::<components::MoveTarget>();
+        world.register::<components::Collider>();
+        world.register::<components::ScreenBounds>();
+        world.register::<components::Velocity>();
+        world.register::<components::CameraFollow>();
 
-        animation::initialize_camera(world);
+        let map_path = format!(
+            "{}/resources/map.ron",
+            env!("CARGO_MANIFEST_DIR")
+        );
 
-        let (sprite_sheet_handle, sprite_sheet_index, sprite_count, sprite_w, sprite_h) = 
+        // Nothing to draw without a renderer, so only the player entity
+        // is created, in the middle of the fake screen
+        if self.headless.is_some() {
+            let mut transform = Transform::default();
+            transform.translation = {
+                let dim = world.read_resource::<ScreenDimensions>();
+                Vector3::new(dim.width() / 2.0, dim.height() / 2.0, 0.0)
+            };
+            TileMap::load(world, &map_path, false);
+            self.create_player(world, transform);
+
+            // No quick save, a simulation shouldn't depend on what was
+            // last saved on the machine running it
+            world.add_resource(RestartRequest::default());
+            return;
+        }
+
+        let camera_path = format!(
+            "{}/resources/camera.ron",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        camera::initialize_camera(world, CameraFollow::load(&camera_path));
+
+        let (sprite_sheet_handle, sprite_count, sprite_w, sprite_h) = 
             load_sprite_sheet(world);
 
+        let animations_path = format!(
+            "{}/resources/animations.ron",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        AnimationClips::load_all(world, &animations_path);
+
+        // Before the player, the tiles are drawn under it
+        TileMap::load(world, &map_path, true);
+
         let sprite_offset_x = sprite_count as f32 * sprite_w / 2.0;
         let sprite_offset_y = sprite_h;
         let (width, height) = {
@@ -144,7 +437,6 @@ impl<'a, 'b> State<CustomGameData<'a, 'b>> for GameplayState {
             world,
             &common_transform,
             sprite_sheet_handle,
-            sprite_sheet_index,
             sprite_count,
             sprite_w,
             sprite_h
@@ -152,13 +444,7 @@ impl<'a, 'b> State<CustomGameData<'a, 'b>> for GameplayState {
 
         // Initialing UI things
         // Load the font into world resources
-        let font = world.read_resource::<Loader>().load(
-            "fonts/Aroania.ttf",
-            TtfFormat,
-            Default::default(),
-            (),
-            &world.read_resource()
-        );
+        let font = load_font(world);
 
         // Create an invisible area for the UI text
         let text_state_transform = UiTransform::new(
@@ -183,47 +469,53 @@ impl<'a, 'b> State<CustomGameData<'a, 'b>> for GameplayState {
             .build();
 
         world.add_resource(GameStateText { text: text_state });
+        world.add_resource(UiFont { font });
+        world.add_resource(RestartRequest::default());
+
+        self.load_on_start(world);
     }
 
-    fn handle_event(&mut self, data: StateData<CustomGameData>, event: Event) -> Trans<CustomGameData<'a, 'b>> {
-        // Pull the `World` instance from the `StateData`
-        let StateData { world, .. } = data;
+    fn handle_event(&mut self, _: StateData<CustomGameData>, event: Event) -> Trans<CustomGameData<'a, 'b>> {
+        // Everything else comes in as actions, see `update`
+        if is_close_requested(&event) {
+            return Trans::Quit
+        }
+        Trans::None
+    }
 
-        // Check if Pause key is being pressed
-        if is_key_down(&event, VirtualKeyCode::Space) {
-            // Change the game state text to reflect the state change
-            let text_resource = world.read_resource::<GameStateText>();
-            let mut ui_text_storage = world.write_storage::<UiText>();
+    fn update(&mut self, data: StateData<CustomGameData>) -> Trans<CustomGameData<'a, 'b>> {
+        data.data.update(&data.world, &[pauser::FIXED_GAMEPLAY, pauser::GAMEPLAY, pauser::ALWAYS]);
 
-            if let Some(ui_text_storage) = ui_text_storage.get_mut(text_resource.text) {
-                ui_text_storage.text = "PAUSED".to_string();
-            }
+        let reloads = match self.dev {
+            Some(ref mut dev) => dev.poll(data.world.read_resource::<Time>().delta_seconds()),
+            None => Vec::new()
+        };
+        self.reload_assets(data.world, &reloads);
 
-            // Switch to the `PausedState`
-            println!("Switching to Pausedstate");
-            return Trans::Push(Box::new(PausedState));
+        let actions = data.world.write_resource::<PressedActions>().take();
+        if let Some(trans) = self.handle_actions(data.world, &actions) {
+            return trans;
         }
 
-        if is_key_down(&event, VirtualKeyCode::I) {
-            let mut player_storage = world.write_storage::<PlayerComponent>();
-            let player = player_storage.get_mut(self.player.unwrap()).expect("Failed to get components for player entity");
-            player.input_state = player.input_state.next();
-            println!("Player input state set to: {:?}", player.input_state);
+        // Played back recordings end the game, after printing where the
+        // player ended up for comparing runs
+        if data.world.read_resource::<ReplayStatus>().finished {
+            if let Some(transform) = data.world.read_storage::<Transform>().get(self.player.unwrap()) {
+                println!("Replay finished, player at {:?}", transform.translation);
+            }
+            return Trans::Quit;
         }
 
-        // Closes the game when Escape is pressed
-        // This doesn't work when in `PausedState`, obviously
-        if is_close_requested(&event) || is_key_down(&event, VirtualKeyCode::Escape) {
-            return Trans::Quit
+        self.frame += 1;
+        if let Some(frames) = self.headless.as_ref().and_then(|headless| headless.frames) {
+            if self.frame >= frames {
+                println!("Headless simulation finished after {} frames", self.frame);
+                return Trans::Quit;
+            }
         }
         Trans::None
     }
 
-    fn update(&mut self, data: StateData<CustomGameData>) -> Trans<CustomGameData<'a, 'b>> {
-        data.data.update(&data.world, true);
-        Trans::None
-    }
-
     fn on_pause(&mut self, data: StateData<CustomGameData>) {
         // Pull the `World` instance from the `StateData`
         let StateData { world, .. } = data;
@@ -236,109 +528,544 @@ impl<'a, 'b> State<CustomGameData<'a, 'b>> for GameplayState {
         // Pull the `World` instance from the `StateData`
         let StateData { world, .. } = data;
 
+        let restart = {
+            let mut restart_request = world.write_resource::<RestartRequest>();
+            let requested = restart_request.requested;
+            restart_request.requested = false;
+            requested
+        };
+        if restart {
+            self.restart(world);
+        }
+
         // Resume the player sprite animation
         self.toggle_player_sprite_animation(world);
     }
 }
 
+impl PausedState {
+    fn new() -> PausedState {
+        PausedState {
+            menu: None,
+            mouse_position: None
+        }
+    }
+
+    // Does what the selected menu entry says
+    fn activate<'a, 'b>(&mut self, world: &mut World) -> Trans<CustomGameData<'a, 'b>> {
+        let entry = match self.menu {
+            Some(ref menu) => menu.selected(),
+            None => return Trans::None
+        };
+
+        match entry {
+            MenuEntry::Resume => self.resume(world),
+            MenuEntry::Restart => {
+                world.write_resource::<RestartRequest>().requested = true;
+                self.resume(world)
+            }
+            MenuEntry::Settings => {
+                set_game_state_text(world, "CONTROLS");
+                Trans::Push(Box::new(ControlsState { menu: None, captured: false, held: Vec::new() }))
+            }
+            MenuEntry::Quit => Trans::Quit
+        }
+    }
+
+    fn resume<'a, 'b>(&mut self, world: &mut World) -> Trans<CustomGameData<'a, 'b>> {
+        // Switch back to the `Gameplay` state
+        set_game_state_text(world, "GAMEPLAY");
+
+        // Switching back to the `GameplayState`
+        println!("Switching to GameplayState");
+        Trans::Pop
+    }
+
+}
+
 impl<'a, 'b> State<CustomGameData<'a, 'b>> for PausedState {
+    fn on_start(&mut self, data: StateData<CustomGameData>) {
+        let StateData { world, .. } = data;
+
+        self.menu = ui_font(world).map(|font| PauseMenu::create(world, &font));
+    }
+
+    fn on_stop(&mut self, data: StateData<CustomGameData>) {
+        let StateData { world, .. } = data;
+
+        if let Some(menu) = self.menu.take() {
+            menu.remove(world);
+        }
+    }
+
+    // The controls menu takes the place of the pause menu while it is open
+    fn on_pause(&mut self, data: StateData<CustomGameData>) {
+        let StateData { world, .. } = data;
+
+        if let Some(menu) = self.menu.take() {
+            menu.remove(world);
+        }
+    }
+
+    fn on_resume(&mut self, data: StateData<CustomGameData>) {
+        let StateData { world, .. } = data;
+
+        set_game_state_text(world, "PAUSED");
+        self.menu = ui_font(world).map(|font| PauseMenu::create(world, &font));
+    }
+
     fn handle_event(&mut self, data: StateData<CustomGameData>, event: Event) -> Trans<CustomGameData<'a, 'b>> {
         let StateData { world, .. } = data;
 
-        if is_key_down(&event, VirtualKeyCode::Space) {
-            // Switch back to the `Gameplay` state when space is pressed.
-            let text_resource = world.read_resource::<GameStateText>();
-            let mut ui_text_storage = world.write_storage::<UiText>();
+        // Clicking activates the entry under the mouse cursor
+        if is_mouse_click(&event, MouseButton::Left) {
+            let clicked = match (self.menu.as_ref(), self.mouse_position) {
+                (Some(menu), Some(position)) => menu.entry_at(world, position),
+                _ => None
+            };
+            if let Some(index) = clicked {
+                self.menu.as_mut().unwrap().select(world, index);
+                return self.activate(world);
+            }
+        }
+
+        if is_close_requested(&event) {
+            return Trans::Quit
+        }
+        Trans::None
+    }
+
+    fn update(&mut self, data: StateData<CustomGameData>) -> Trans<CustomGameData<'a, 'b>> {
+        data.data.update(&data.world, &[pauser::ALWAYS]);
+
+        let StateData { world, .. } = data;
+
+        // Select the entry under the mouse cursor when the mouse moves
+        let mouse_position = world.read_resource::<InputHandler<String, String>>().mouse_position();
+        if mouse_position != self.mouse_position {
+            self.mouse_position = mouse_position;
+            if let (Some(menu), Some(position)) = (self.menu.as_mut(), mouse_position) {
+                if let Some(index) = menu.entry_at(world, position) {
+                    menu.select(world, index);
+                }
+            }
+        }
+
+        let actions = world.write_resource::<PressedActions>().take();
+        for action in actions {
+            match action {
+                "menu_select" => return self.activate(world),
+                // Switch back to the `Gameplay` state
+                "pause" => return self.resume(world),
+                // Closes the game, paused or not
+                "quit" => return Trans::Quit,
+                _ => {}
+            }
 
-            if let Some(ui_text_storage) = ui_text_storage.get_mut(text_resource.text) {
-                ui_text_storage.text = "GAMEPLAY".to_string();
+            if let Some(ref mut menu) = self.menu {
+                if action == "menu_up" {
+                    menu.select_previous(world);
+                } else if action == "menu_down" {
+                    menu.select_next(world);
+                }
             }
+        }
+        Trans::None
+    }
+}
+
+impl<'a, 'b> State<CustomGameData<'a, 'b>> for ControlsState {
+    fn on_start(&mut self, data: StateData<CustomGameData>) {
+        let StateData { world, .. } = data;
+
+        self.menu = ui_font(world).map(|font| ControlsMenu::create(world, &font));
+    }
+
+    fn on_stop(&mut self, data: StateData<CustomGameData>) {
+        let StateData { world, .. } = data;
+
+        if let Some(menu) = self.menu.take() {
+            menu.remove(world);
+        }
+    }
+
+    fn handle_event(&mut self, data: StateData<CustomGameData>, event: Event) -> Trans<CustomGameData<'a, 'b>> {
+        let StateData { world, .. } = data;
 
-            // Switching back to the `GameplayState`
-            println!("Switching to GameplayState");
-            return Trans::Pop;
+        if is_close_requested(&event) {
+            return Trans::Quit;
+        }
+
+        let menu = match self.menu {
+            Some(ref mut menu) => menu,
+            None => return Trans::None
+        };
+
+        // The next key or mouse button pressed becomes the binding. Escape
+        // is the one fixed key here, it cancels, since whatever else is
+        // pressed gets bound.
+        if menu.is_capturing() {
+            if is_key_down(&event, VirtualKeyCode::Escape) {
+                menu.set_capturing(world, false);
+                self.captured = true;
+            } else if let Some(button) = bindings::pressed_button(&event) {
+                let conflict = {
+                    let mut input = world.write_resource::<InputHandler<String, String>>();
+                    let conflict = bindings::conflict(&input.bindings, menu.selected_action(), &button);
+                    if conflict.is_none() {
+                        bindings::rebind(&mut input.bindings, menu.selected_action(), button);
+                        if let Err(err) = bindings::save(&input.bindings) {
+                            println!("Failed to save the controls: {:?}", err);
+                        }
+                    }
+                    conflict
+                };
+                match conflict {
+                    Some(other) => menu.reject(world, format!("{} is used by {}", bindings::button_name(&button), other)),
+                    None => menu.set_capturing(world, false)
+                }
+                self.captured = true;
+            }
         }
         Trans::None
     }
 
     fn update(&mut self, data: StateData<CustomGameData>) -> Trans<CustomGameData<'a, 'b>> {
-        data.data.update(&data.world, false);
+        data.data.update(&data.world, &[pauser::ALWAYS]);
+
+        let StateData { world, .. } = data;
+
+        // Taken here so the paused state doesn't get them when this closes
+        let actions = world.write_resource::<PressedActions>().take();
+        if self.captured {
+            self.captured = false;
+            return Trans::None;
+        }
+
+        let menu = match self.menu {
+            Some(ref mut menu) => menu,
+            None => return Trans::None
+        };
+        if menu.is_capturing() {
+            // Keys are captured in `handle_event`, gamepad buttons here
+            let down = held_gamepad_buttons(world);
+            let pressed = down.iter().find(|button| !self.held.contains(button)).cloned();
+            self.held = down;
+
+            if let Some(button) = pressed {
+                let conflict = {
+                    let mut gamepad_config = world.write_resource::<GamepadConfig>();
+                    let conflict = bindings::gamepad_conflict(&gamepad_config, menu.selected_action(), button);
+                    if conflict.is_none() {
+                        bindings::rebind_gamepad(&mut gamepad_config, menu.selected_action(), button);
+                        if let Err(err) = bindings::save_gamepad(&gamepad_config) {
+                            println!("Failed to save the gamepad controls: {:?}", err);
+                        }
+                    }
+                    conflict
+                };
+                match conflict {
+                    Some(other) => menu.reject(world, format!("{:?} is used by {}", button, other)),
+                    None => menu.set_capturing(world, false)
+                }
+            }
+            return Trans::None;
+        }
+        for action in actions {
+            match action {
+                "menu_up" => menu.select_previous(world),
+                "menu_down" => menu.select_next(world),
+                "menu_select" => {
+                    self.held = held_gamepad_buttons(world);
+                    menu.set_capturing(world, true);
+                }
+                // Back to the pause menu
+                "pause" | "quit" => return Trans::Pop,
+                _ => {}
+            }
+        }
         Trans::None
     }
 }
 
-fn load_sprite_sheet(world: &mut World) -> (SpriteSheetHandle, u64, usize, f32, f32) {
-    let sprite_sheet_index = 0;
-    let texture = sprite::load("textures/walkingwhiteball.png", world);
-    world.write_resource::<MaterialTextureSet>().insert(sprite_sheet_index, texture);
-
-    let sprite_w = 64.0;
-    let sprite_h = 64.0;
-    let sprite_sheet_definition = SpriteSheetDefinition::new(sprite_w, sprite_h, 1, 6, false);
-    let sprite_sheet = sprite_sheet_loader::load(sprite_sheet_index, &sprite_sheet_definition);
-    let sprite_count = sprite_sheet.sprites.len();
-
-    let sprite_sheet_handle = {
-        let loader = world.read_resource::<Loader>();
-        loader.load_from_data(
-            sprite_sheet,
-            (),
-            &world.read_resource::<AssetStorage<SpriteSheet>>()
-        )
+// Moves anything a reloaded map put inside a solid tile to the nearest
+// free spot, so it doesn't get stuck there
+fn push_out_of_walls(world: &mut World) {
+    let tile_map = match world.res.try_fetch::<TileMap>() {
+        Some(tile_map) => tile_map,
+        None => return
     };
+    let colliders = world.read_storage::<Collider>();
+    let mut transforms = world.write_storage::<Transform>();
 
+    for (collider, transform) in (&colliders, &mut transforms).join() {
+        if !tile_map.overlaps_solid(transform.translation, collider.half_extents) {
+            continue;
+        }
+        match tile_map.nearest_free(transform.translation, collider.half_extents) {
+            Some(position) => {
+                println!("Moved out of a wall from {:?} to {:?}", transform.translation, position);
+                transform.translation = position;
+            }
+            None => println!("Stuck in a wall at {:?}, the map has no room left", transform.translation)
+        }
+    }
+}
+
+fn held_gamepad_buttons(world: &World) -> Vec<GamepadButton> {
     world
-        .write_resource::<SpriteSheetSet>()
-        .insert(sprite_sheet_index, sprite_sheet_handle.clone());
+        .read_resource::<GamepadState>()
+        .active_pad()
+        .map(|pad| pad.pressed_buttons())
+        .unwrap_or_default()
+}
 
-    (
-        sprite_sheet_handle,
-        sprite_sheet_index,
-        sprite_count,
-        sprite_w,
-        sprite_h
+fn resource_path(name: &str) -> String {
+    format!("{}/resources/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn load_font(world: &World) -> FontHandle {
+    world.read_resource::<Loader>().load(
+        "fonts/Aroania.ttf",
+        TtfFormat,
+        Default::default(),
+        (),
+        &world.read_resource()
     )
 }
 
-fn main() -> Result<(), amethyst::Error> {
-    amethyst::start_logger(Default::default());
+// Loads the clips again and restarts the animation of every entity with
+// the new ones
+fn reload_animations(world: &mut World) {
+    AnimationClips::load_all(world, &resource_path("animations.ron"));
 
-    let path = format!(
-        "{}/resources/display_config.ron",
-        env!("CARGO_MANIFEST_DIR")
-    );
-    let config = DisplayConfig::load(&path);
+    let clips = world.read_resource::<AnimationClips>();
+    let mut controllers = world.write_storage::<AnimationController>();
+    let mut control_sets = world.write_storage::<AnimationControlSet<u32, SpriteRender>>();
+    for (controller, control_set) in (&mut controllers, &mut control_sets).join() {
+        if let Some(current) = controller.current {
+            control_set.abort(current);
+        }
+        *controller = AnimationController::from_clips(&clips);
+    }
+}
+
+// Checks if the mouse `button` was just pressed
+fn is_mouse_click(event: &Event, button: MouseButton) -> bool {
+    match *event {
+        Event::WindowEvent { ref event, .. } => match *event {
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: pressed, .. } => pressed == button,
+            _ => false
+        },
+        _ => false
+    }
+}
+
+fn load_sprite_sheet(world: &mut World) -> (SpriteSheetHandle, usize, f32, f32) {
+    SpriteSheetRegistry::load_all(world, "textures");
+
+    let registry = world.read_resource::<SpriteSheetRegistry>();
+    let sheet = registry
+        .get("walkingwhiteball")
+        .expect("Sprite sheet definition `textures/walkingwhiteball.ron` is missing");
+
+    (
+        sheet.handle.clone(),
+        sheet.definition.sprite_count(),
+        sheet.definition.sprite_w,
+        sheet.definition.sprite_h
+    )
+}
+
+// Real controllers when built with the `gamepad` feature, otherwise
+// (or when no controller backend is available) the simulated source
+// that never produces anything on its own.
+fn gamepad_source() -> Box<dyn GamepadEventSource> {
+    #[cfg(feature = "gamepad")]
+    {
+        match gamepad::GilrsSource::new() {
+            Ok(source) => return Box::new(source),
+            Err(err) => println!("Gamepad support unavailable: {:?}", err)
+        }
+    }
+    Box::new(SimulatedGamepad::new())
+}
 
+// Every system of the game, without a renderer when `headless` is set
+fn game_data<'a, 'b>(
+    headless: Option<&HeadlessConfig>,
+    replay: ReplayMode
+) -> Result<CustomGameDataBuilder<'a, 'b>, amethyst::Error> {
     let key_bindings_path = format!(
         "{}/resources/input.ron",
         env!("CARGO_MANIFEST_DIR")
     );
 
-    let pipe = Pipeline::build().with_stage(
-        Stage::with_backbuffer()
-            // Lets game the background color nice purple-y
-            .clear_target([0.408, 0.361, 0.733, 1.0], 1.0)
-            // Let's draw sprites
-            .with_pass(DrawSprite::new().with_transparency(ColorMask::all(), ALPHA, None))
-            // Let's draw the UI too!
-            .with_pass(DrawUi::new())
-    );
+    // Gameplay systems run before the ones running in every state,
+    // like they did back when `running` was dispatched before `base`
+    let mut game_data = CustomGameDataBuilder::default()
+        .with_fixed_group(pauser::FIXED_GAMEPLAY, 1.0 / 60.0)
+        .with_group(pauser::GAMEPLAY)
+        .with_group(pauser::ALWAYS);
+
+    game_data = match headless {
+        Some(headless) => game_data
+            .with_fake_screen(headless.screen_width, headless.screen_height)
+            .with_base_bundle(TransformBundle::new())?
+            .with_shared(systems::GamepadSystem::new(Box::new(SimulatedGamepad::new())), "gamepad_system", &[]),
+        None => {
+            let path = format!(
+                "{}/resources/display_config.ron",
+                env!("CARGO_MANIFEST_DIR")
+            );
+            let config = DisplayConfig::load(&path);
+
+            let pipe = Pipeline::build().with_stage(
+                Stage::with_backbuffer()
+                    // Lets game the background color nice purple-y
+                    .clear_target([0.408, 0.361, 0.733, 1.0], 1.0)
+                    // Let's draw sprites
+                    .with_pass(DrawSprite::new().with_transparency(ColorMask::all(), ALPHA, None))
+                    // Let's draw the UI too!
+                    .with_pass(DrawUi::new())
+            );
+
+            game_data
+                .with_base_bundle(AnimationBundle::<u32, SpriteRender>::new(
+                    "animation_control_system",
+                    "sampler_interpolation_system"
+                ))?
+                .with_base_bundle(TransformBundle::new().with_dep(&["animation_control_system", "sampler_interpolation_system"]))?
+                .with_base_bundle(RenderBundle::new(pipe, Some(config)).with_sprite_sheet_processor())?
+                .with_base_bundle(UiBundle::<String, String>::new())?
+                .with_shared(systems::GamepadSystem::new(gamepad_source()), "gamepad_system", &[])
+        }
+    };
+
+    let game_data = game_data
+        .with_shared_bundle(InputBundle::<String, String>::new().with_bindings_from_file(&key_bindings_path)?)?
+        .with_shared(systems::ActionSystem::default(), "action_system", &["input_system", "gamepad_system"]);
+    let game_data = match replay {
+        // A played back recording keeps the tuning it was recorded with,
+        // edits to `player.ron` don't get reloaded until the next run
+        ReplayMode::Playback(_) => game_data,
+        _ => game_data.with_shared(
+            systems::PlayerConfigSystem::new(player_config::player_config_path()),
+            "player_config_system",
+            &[]
+        )
+    };
+
+    Ok(game_data
+        // Movement is simulated in fixed steps, one input frame per step.
+        // Only gameplay steps get recorded, so a replay isn't thrown off by pauses.
+        .with_bundle(pauser::FIXED_GAMEPLAY, systems::FixedGameplayBundle::new(replay))?
+        // Drawn positions are interpolated between the last two steps
+        .with_running(systems::InterpolationSystem, "interpolation_system", &[])
+        .with_running(systems::AnimationControllerSystem, "animation_controller_system", &["interpolation_system"])
+        .with_running(systems::CameraFollowSystem, "camera_follow_system", &["interpolation_system"]))
+}
+
+fn main() -> Result<(), amethyst::Error> {
+    amethyst::start_logger(Default::default());
+
+    let gamepad_config = GamepadConfig::load(&gamepad::gamepad_config_path());
+
+    let player_config = PlayerConfig::load(&player_config::player_config_path());
+
+    let headless = HeadlessConfig::from_args();
+    let game_data = game_data(headless.as_ref(), ReplayMode::from_args())?;
 
-    let game_data = CustomGameDataBuilder::default()
-        .with_base_bundle(AnimationBundle::<u32, SpriteRender>::new(
-            "animation_control_system",
-            "sampler_interpolation_system"
-        ))?
-        .with_base_bundle(TransformBundle::new().with_dep(&["animation_control_system", "sampler_interpolation_system"]))?
-        .with_base_bundle(RenderBundle::new(pipe, Some(config)).with_sprite_sheet_processor())?
-        .with_base_bundle(InputBundle::<String, String>::new().with_bindings_from_file(&key_bindings_path)?)?
-        .with_base_bundle(UiBundle::<String, String>::new())?
-        .with_running_bundle(InputBundle::<String, String>::new().with_bindings_from_file(&key_bindings_path)?)?
-        .with_running(systems::MovePlayerSystem::default(), "move_player_system", &["input_system"]);
-
-    let mut game = Application::build("./", GameplayState::new())?
+    let mut game = Application::build("./", GameplayState::new(headless))?
+        .with_resource(gamepad_config)
+        .with_resource(player_config)
         .build(game_data)?;
     game.run();
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs the gameplay state headless, pressing `pause` on one frame and
+    // resuming right after, and checks the world once the game quits
+    struct HeadlessRun {
+        gameplay: GameplayState,
+        pause_on: Option<u64>,
+        check: fn(&mut World, Entity)
+    }
+
+    impl<'a, 'b> State<CustomGameData<'a, 'b>> for HeadlessRun {
+        fn on_start(&mut self, data: StateData<CustomGameData>) {
+            self.gameplay.on_start(data);
+        }
+
+        fn on_stop(&mut self, data: StateData<CustomGameData>) {
+            (self.check)(data.world, self.gameplay.player.unwrap());
+        }
+
+        fn on_pause(&mut self, data: StateData<CustomGameData>) {
+            self.gameplay.on_pause(data);
+        }
+
+        fn on_resume(&mut self, data: StateData<CustomGameData>) {
+            self.gameplay.on_resume(data);
+        }
+
+        fn update(&mut self, data: StateData<CustomGameData>) -> Trans<CustomGameData<'a, 'b>> {
+            let StateData { world, data } = data;
+
+            let pause = self.pause_on == Some(self.gameplay.frame);
+            if pause {
+                world.write_resource::<PressedActions>().press("pause");
+            }
+            let trans = self.gameplay.update(StateData::new(&mut *world, &mut *data));
+            // Taken by the `PausedState` on its first update
+            if pause {
+                world.write_resource::<PressedActions>().press("pause");
+            }
+            trans
+        }
+    }
+
+    fn run_headless(frames: u64, pause_on: Option<u64>, check: fn(&mut World, Entity)) {
+        let headless = HeadlessConfig {
+            frames: Some(frames),
+            ..HeadlessConfig::default()
+        };
+        let game_data = game_data(Some(&headless), ReplayMode::Live).unwrap();
+        let state = HeadlessRun {
+            gameplay: GameplayState::new(Some(headless)),
+            pause_on,
+            check
+        };
+
+        let mut game = Application::build("./", state)
+            .unwrap()
+            .with_resource(GamepadConfig::default())
+            .with_resource(PlayerConfig::default())
+            .build(game_data)
+            .unwrap();
+        game.run();
+    }
+
+    // Without input the player stays in the middle of the fake screen,
+    // whatever quick save there is on the machine
+    fn assert_in_the_middle(world: &mut World, player: Entity) {
+        let transforms = world.read_storage::<Transform>();
+        let translation = transforms.get(player).unwrap().translation;
+        assert_eq!((translation.x, translation.y), (400.0, 300.0));
+    }
+
+    #[test]
+    fn headless_simulation_runs_its_frames() {
+        run_headless(30, None, assert_in_the_middle);
+    }
+
+    #[test]
+    fn pausing_headless() {
+        run_headless(30, Some(10), assert_in_the_middle);
+    }
+}
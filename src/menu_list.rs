@@ -0,0 +1,114 @@
+use amethyst::ecs::prelude::Entity;
+use amethyst::prelude::*;
+use amethyst::ui::{Anchor, FontHandle, UiText, UiTransform};
+
+const NORMAL_COLOR: [f32; 4] = [0.086, 0.078, 0.235, 1.0];
+const SELECTED_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// Size of the rows of a `MenuList`
+pub struct MenuStyle {
+    pub width: f32,
+    pub row_height: f32,
+    pub font_size: f32
+}
+
+/// Column of UI texts under the state text with one of them selected,
+/// what the pause menu and the controls menu are made of
+pub struct MenuList {
+    // Prefix of the UI transform ids, e.g. `pause_menu`
+    name: String,
+    // UI text entity of every row, top first
+    entities: Vec<Entity>,
+    selected: usize
+}
+
+impl MenuList {
+    pub fn create(world: &mut World, font: &FontHandle, name: &str, labels: &[String], style: &MenuStyle) -> Self {
+        let entities = labels
+            .iter()
+            .enumerate()
+            .map(|(n, label)| {
+                let transform = UiTransform::new(
+                    format!("{}_{}", name, n),
+                    Anchor::Middle,
+                    0.0, 70.0 + style.row_height * n as f32, 1.0,
+                    style.width, style.row_height,
+                    n as i32
+                );
+
+                world
+                    .create_entity()
+                    .with(transform)
+                    .with(UiText::new(
+                        font.clone(),
+                        label.clone(),
+                        NORMAL_COLOR,
+                        style.font_size
+                    ))
+                    .build()
+            })
+            .collect();
+
+        let mut list = MenuList {
+            name: name.to_string(),
+            entities,
+            selected: 0
+        };
+        list.select(world, 0);
+        list
+    }
+
+    pub fn remove(self, world: &mut World) {
+        if let Err(err) = world.delete_entities(&self.entities) {
+            println!("Failed to remove the {}: {:?}", self.name, err);
+        }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, world: &World, index: usize) {
+        self.selected = index % self.entities.len();
+
+        let mut ui_text_storage = world.write_storage::<UiText>();
+        for (n, entity) in self.entities.iter().enumerate() {
+            if let Some(ui_text) = ui_text_storage.get_mut(*entity) {
+                ui_text.color = if n == self.selected {
+                    SELECTED_COLOR
+                } else {
+                    NORMAL_COLOR
+                };
+            }
+        }
+    }
+
+    pub fn select_next(&mut self, world: &World) {
+        let next = self.selected + 1;
+        self.select(world, next);
+    }
+
+    pub fn select_previous(&mut self, world: &World) {
+        let previous = self.selected + self.entities.len() - 1;
+        self.select(world, previous);
+    }
+
+    pub fn set_label(&self, world: &World, index: usize, label: String) {
+        let mut ui_text_storage = world.write_storage::<UiText>();
+        if let Some(ui_text) = self.entities.get(index).and_then(|entity| ui_text_storage.get_mut(*entity)) {
+            ui_text.text = label;
+        }
+    }
+
+    /// Index of the row under the mouse cursor
+    pub fn entry_at(&self, world: &World, (x, y): (f64, f64)) -> Option<usize> {
+        let ui_transforms = world.read_storage::<UiTransform>();
+        self.entities.iter().position(|entity| {
+            ui_transforms.get(*entity).map_or(false, |transform| {
+                // Global position is the center of the element
+                (x as f32 - transform.global_x).abs() <= transform.width / 2.0
+                    && (y as f32 - transform.global_y).abs() <= transform.height / 2.0
+            })
+        })
+    }
+}
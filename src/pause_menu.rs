@@ -0,0 +1,73 @@
+use amethyst::prelude::*;
+use amethyst::ui::FontHandle;
+
+use menu_list::{MenuList, MenuStyle};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MenuEntry {
+    Resume,
+    Restart,
+    Settings,
+    Quit
+}
+
+impl MenuEntry {
+    pub fn all() -> &'static [MenuEntry] {
+        &[MenuEntry::Resume, MenuEntry::Restart, MenuEntry::Settings, MenuEntry::Quit]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match *self {
+            MenuEntry::Resume => "Resume",
+            MenuEntry::Restart => "Restart",
+            MenuEntry::Settings => "Settings",
+            MenuEntry::Quit => "Quit"
+        }
+    }
+}
+
+/// The entries shown under the "PAUSED" text
+pub struct PauseMenu {
+    // One row per entry, in the same order as `MenuEntry::all`
+    list: MenuList
+}
+
+impl PauseMenu {
+    pub fn create(world: &mut World, font: &FontHandle) -> Self {
+        let labels: Vec<String> = MenuEntry::all().iter().map(|entry| entry.label().to_string()).collect();
+        let style = MenuStyle {
+            width: 230.0,
+            row_height: 50.0,
+            font_size: 40.0
+        };
+
+        PauseMenu {
+            list: MenuList::create(world, font, "pause_menu", &labels, &style)
+        }
+    }
+
+    pub fn remove(self, world: &mut World) {
+        self.list.remove(world);
+    }
+
+    pub fn selected(&self) -> MenuEntry {
+        MenuEntry::all()[self.list.selected()]
+    }
+
+    pub fn select(&mut self, world: &mut World, index: usize) {
+        self.list.select(world, index);
+    }
+
+    pub fn select_next(&mut self, world: &mut World) {
+        self.list.select_next(world);
+    }
+
+    pub fn select_previous(&mut self, world: &mut World) {
+        self.list.select_previous(world);
+    }
+
+    /// Index of the entry under the mouse cursor
+    pub fn entry_at(&self, world: &World, position: (f64, f64)) -> Option<usize> {
+        self.list.entry_at(world, position)
+    }
+}
@@ -4,25 +4,108 @@ use amethyst::core::SystemBundle;
 use amethyst::ecs::prelude::{Dispatcher, DispatcherBuilder, System, World};
 use amethyst::{DataInit, Error, Result};
 use amethyst::core::ThreadPool;
+use amethyst::core::timing::Time;
+use amethyst::renderer::ScreenDimensions;
 
+// Dispatcher groups used by the game states
+// Runs in every state
+pub const ALWAYS: &str = "always";
+// Runs only while the game itself is being played
+pub const GAMEPLAY: &str = "gameplay";
+// Like `GAMEPLAY`, but with a fixed timestep
+pub const FIXED_GAMEPLAY: &str = "fixed_gameplay";
+
+// Most fixed steps run in one update, the rest of the time is dropped
+// so a slow frame can't snowball into even slower ones
+const MAX_FIXED_STEPS: u32 = 8;
+
+/// Timing of the fixed timestep groups, read by the systems in them
+#[derive(Clone, Debug)]
+pub struct FixedStep {
+    // Seconds simulated by one dispatch of a fixed group
+    pub timestep: f32,
+    // How far the time is between the last two fixed steps, 0.0 - 1.0,
+    // for interpolating what is drawn
+    pub alpha: f32,
+}
+
+impl Default for FixedStep {
+    fn default() -> Self {
+        FixedStep {
+            timestep: 1.0 / 60.0,
+            alpha: 0.0,
+        }
+    }
+}
+
+struct Group<'a, 'b> {
+    name: String,
+    dispatcher: Dispatcher<'a, 'b>,
+    // Fixed timestep groups are dispatched once per `timestep` of
+    // accumulated frame time instead of once per update
+    timestep: Option<f32>,
+    accumulator: f32,
+}
+
+impl<'a, 'b> Group<'a, 'b> {
+    fn dispatch(&mut self, world: &World) {
+        let timestep = match self.timestep {
+            Some(timestep) => timestep,
+            None => {
+                self.dispatcher.dispatch(&world.res);
+                return;
+            }
+        };
+
+        self.accumulator += world.read_resource::<Time>().delta_seconds();
+        world.write_resource::<FixedStep>().timestep = timestep;
+
+        let mut steps = 0;
+        while self.accumulator >= timestep {
+            if steps == MAX_FIXED_STEPS {
+                self.accumulator = 0.0;
+                break;
+            }
+            self.dispatcher.dispatch(&world.res);
+            self.accumulator -= timestep;
+            steps += 1;
+        }
+
+        world.write_resource::<FixedStep>().alpha = self.accumulator / timestep;
+    }
+}
+
+/// Named dispatcher groups, a state picks the ones that run in its `update`.
+/// The shared stage runs before them on every update.
 pub struct CustomGameData<'a, 'b> {
-    pub base: Dispatcher<'a, 'b>,
-    pub running: Dispatcher<'a, 'b>,
+    shared: Dispatcher<'a, 'b>,
+    // In the order the groups were added to the builder
+    groups: Vec<Group<'a, 'b>>,
 }
 
 impl<'a, 'b> CustomGameData<'a, 'b> {
-    /// Update game data
-    pub fn update(&mut self, world: &World, running: bool) {
-        if running {
-            self.running.dispatch(&world.res);
+    /// Update game data, dispatching the shared stage and the `active` groups
+    pub fn update(&mut self, world: &World, active: &[&str]) {
+        self.shared.dispatch(&world.res);
+        for group in self.groups.iter_mut() {
+            if active.contains(&group.name.as_str()) {
+                group.dispatch(world);
+            }
         }
-        self.base.dispatch(&world.res);
     }
 }
 
+pub struct GroupBuilder<'a, 'b> {
+    pub name: String,
+    pub builder: DispatcherBuilder<'a, 'b>,
+    pub timestep: Option<f32>,
+}
+
 pub struct CustomGameDataBuilder<'a, 'b> {
-    pub base: DispatcherBuilder<'a, 'b>,
-    pub running: DispatcherBuilder<'a, 'b>,
+    pub shared: DispatcherBuilder<'a, 'b>,
+    pub groups: Vec<GroupBuilder<'a, 'b>>,
+    // Screen size to pretend to have when there is no renderer
+    pub fake_screen: Option<(u32, u32)>,
 }
 
 impl<'a, 'b> Default for CustomGameDataBuilder<'a, 'b> {
@@ -34,65 +117,140 @@ impl<'a, 'b> Default for CustomGameDataBuilder<'a, 'b> {
 impl<'a, 'b> CustomGameDataBuilder<'a, 'b> {
     pub fn new() -> Self {
         CustomGameDataBuilder {
-            base: DispatcherBuilder::new(),
-            running: DispatcherBuilder::new(),
+            shared: DispatcherBuilder::new(),
+            groups: Vec::new(),
+            fake_screen: None,
         }
     }
 
-    pub fn with_base<S>(mut self, system: S, name: &str, dependencies: &[&str]) -> Self
+    /// Headless configuration, inserts a `ScreenDimensions` resource
+    /// for the systems that need one since no `RenderBundle` does it.
+    pub fn with_fake_screen(mut self, width: u32, height: u32) -> Self {
+        self.fake_screen = Some((width, height));
+        self
+    }
+
+    /// Adds a system to the shared stage. It runs once per update before
+    /// every group, so systems in the groups don't need to (and can't)
+    /// list it in their dependencies to see its results.
+    pub fn with_shared<S>(mut self, system: S, name: &str, dependencies: &[&str]) -> Self
     where
         for<'c> S: System<'c> + Send + 'a,
     {
-        self.base.add(system, name, dependencies);
+        self.shared.add(system, name, dependencies);
         self
     }
 
-    pub fn with_base_bundle<B>(mut self, bundle: B) -> Result<Self>
+    pub fn with_shared_bundle<B>(mut self, bundle: B) -> Result<Self>
     where
         B: SystemBundle<'a, 'b>,
     {
         bundle
-            .build(&mut self.base)
+            .build(&mut self.shared)
             .map_err(|err| Error::Core(err))?;
         Ok(self)
     }
 
-    pub fn with_running<S>(mut self, system: S, name: &str, dependencies: &[&str]) -> Self
+    /// Adds an empty group. Groups are dispatched in the order they are
+    /// added, either with this or by the first system or bundle put in them.
+    pub fn with_group(mut self, group: &str) -> Self {
+        self.group(group);
+        self
+    }
+
+    /// Adds an empty group that is dispatched once for every `timestep`
+    /// seconds of frame time. Systems in it get the step length from the
+    /// `FixedStep` resource.
+    pub fn with_fixed_group(mut self, group: &str, timestep: f32) -> Self {
+        self.group(group);
+        if let Some(fixed) = self.groups.iter_mut().find(|builder| builder.name == group) {
+            fixed.timestep = Some(timestep);
+        }
+        self
+    }
+
+    pub fn with<S>(mut self, group: &str, system: S, name: &str, dependencies: &[&str]) -> Self
     where
         for<'c> S: System<'c> + Send + 'a,
     {
-        self.running.add(system, name, dependencies);
+        self.group(group).add(system, name, dependencies);
         self
     }
 
-    pub fn with_running_bundle<B>(mut self, bundle: B) -> Result<Self>
+    pub fn with_bundle<B>(mut self, group: &str, bundle: B) -> Result<Self>
     where
         B: SystemBundle<'a, 'b>,
     {
         bundle
-            .build(&mut self.running)
+            .build(self.group(group))
             .map_err(|err| Error::Core(err))?;
         Ok(self)
     }
+
+    pub fn with_base_bundle<B>(self, bundle: B) -> Result<Self>
+    where
+        B: SystemBundle<'a, 'b>,
+    {
+        self.with_bundle(ALWAYS, bundle)
+    }
+
+    pub fn with_running<S>(self, system: S, name: &str, dependencies: &[&str]) -> Self
+    where
+        for<'c> S: System<'c> + Send + 'a,
+    {
+        self.with(GAMEPLAY, system, name, dependencies)
+    }
+
+    // Dispatcher builder of the group, created when missing
+    fn group(&mut self, group: &str) -> &mut DispatcherBuilder<'a, 'b> {
+        let index = match self.groups.iter().position(|builder| builder.name == group) {
+            Some(index) => index,
+            None => {
+                self.groups.push(GroupBuilder {
+                    name: group.to_string(),
+                    builder: DispatcherBuilder::new(),
+                    timestep: None,
+                });
+                self.groups.len() - 1
+            }
+        };
+        &mut self.groups[index].builder
+    }
 }
 
 impl<'a, 'b> DataInit<CustomGameData<'a, 'b>> for CustomGameDataBuilder<'a, 'b> {
     fn build(self, world: &mut World) -> CustomGameData<'a, 'b> {
+        if let Some((width, height)) = self.fake_screen {
+            world.add_resource(ScreenDimensions::new(width, height, 1.0));
+        }
+
         #[cfg(not(no_threading))]
         let pool = world.read_resource::<ThreadPool>().clone();
 
         #[cfg(not(no_threading))]
-        let mut base = self.base.with_pool(pool.clone()).build();
+        let mut shared = self.shared.with_pool(pool.clone()).build();
         #[cfg(no_threading)]
-        let mut base = self.base.build();
-        base.setup(&mut world.res);
+        let mut shared = self.shared.build();
+        shared.setup(&mut world.res);
 
-        #[cfg(not(no_threading))]
-        let mut running = self.running.with_pool(pool.clone()).build();
-        #[cfg(no_threading)]
-        let mut running = self.running.build();
-        running.setup(&mut world.res);
+        world.add_resource(FixedStep::default());
+
+        let mut groups = Vec::with_capacity(self.groups.len());
+        for GroupBuilder { name, builder, timestep } in self.groups {
+            #[cfg(not(no_threading))]
+            let mut dispatcher = builder.with_pool(pool.clone()).build();
+            #[cfg(no_threading)]
+            let mut dispatcher = builder.build();
+            dispatcher.setup(&mut world.res);
+
+            groups.push(Group {
+                name,
+                dispatcher,
+                timestep,
+                accumulator: 0.0,
+            });
+        }
 
-        CustomGameData { base, running }
+        CustomGameData { shared, groups }
     }
 }
@@ -0,0 +1,84 @@
+use amethyst::core::transform::Transform;
+use components::{Facing, FacingMode, InputState, PlayerComponent};
+use steering::Easing;
+
+pub fn player_config_path() -> String {
+    format!("{}/resources/player.ron", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Tuning of the walker, loaded from `resources/player.ron` and reloaded
+/// by the `PlayerConfigSystem` whenever the file changes
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerConfig {
+    pub speed: f32,
+    // Speed gained per second when starting to move with the keyboard or
+    // a controller, 0.0 starts at full speed
+    pub acceleration: f32,
+    // Speed lost per second after letting go, 0.0 stops right away
+    pub friction: f32,
+    // Distance from the mouse target that counts as arrived
+    pub arrival_tolerance: f32,
+    // Distance from the mouse target where slowing down starts, 0.0 for never
+    pub slowing_radius: f32,
+    pub arrival_easing: Easing,
+    // Whether the walker turns in four or eight directions
+    pub facing: FacingMode,
+    // Input mode on start and after restarting
+    pub input_state: InputState,
+    // Where the walker starts. With `None` it's where its sprite is laid
+    // out, up and left of the middle of the screen, or right in the middle
+    // of the fake screen when headless.
+    pub spawn_position: Option<(f32, f32)>
+}
+
+impl Default for PlayerConfig {
+    fn default() -> PlayerConfig {
+        let player = PlayerComponent::default();
+        PlayerConfig {
+            speed: player.speed,
+            acceleration: player.acceleration,
+            friction: player.friction,
+            arrival_tolerance: player.arrival_tolerance,
+            slowing_radius: player.slowing_radius,
+            arrival_easing: player.arrival_easing,
+            facing: FacingMode::EightWay,
+            input_state: player.input_state,
+            spawn_position: None
+        }
+    }
+}
+
+impl PlayerConfig {
+    /// A player as it starts the game
+    pub fn player(&self) -> PlayerComponent {
+        let mut player = PlayerComponent {
+            input_state: self.input_state.clone(),
+            ..PlayerComponent::default()
+        };
+        self.apply(&mut player);
+        player
+    }
+
+    /// Which way a player looks as it starts the game
+    pub fn facing(&self) -> Facing {
+        Facing::new(self.facing)
+    }
+
+    /// Changes the tuning of a player that is already playing, leaving
+    /// its state and input mode alone
+    pub fn apply(&self, player: &mut PlayerComponent) {
+        player.speed = self.speed;
+        player.acceleration = self.acceleration;
+        player.friction = self.friction;
+        player.arrival_tolerance = self.arrival_tolerance;
+        player.slowing_radius = self.slowing_radius;
+        player.arrival_easing = self.arrival_easing;
+    }
+}
+
+/// Where the player was created, restarting puts it back there
+#[derive(Clone, Debug, Default)]
+pub struct PlayerSpawn {
+    pub transform: Option<Transform>
+}
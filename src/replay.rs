@@ -0,0 +1,142 @@
+use std::env;
+use std::fs;
+use std::mem;
+use std::path::Path;
+
+use amethyst::config::{Config, ConfigError};
+
+use player_config::PlayerConfig;
+use save::PlayerSnapshot;
+
+// Bump when the recording format changes
+pub const RECORDING_VERSION: u32 = 4;
+
+/// Everything `MovePlayerSystem` needs from the input for one frame
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InputFrame {
+    pub delta_seconds: f32,
+    pub horizontal_movement: f64,
+    pub vertical_movement: f64,
+    // Cursor in world coordinates, so the camera moving differently
+    // doesn't change where a played back click lands
+    pub mouse_position: Option<(f32, f32)>,
+    pub left_mouse_down: bool,
+    #[serde(default)]
+    pub right_mouse_down: bool,
+    // Either shift key, for queueing waypoints
+    #[serde(default)]
+    pub shift_down: bool,
+    // Stick position with the dead zone and curve already applied
+    pub gamepad_movement: (f32, f32),
+    // Gameplay actions taken on this step, see `PlayerActionSystem`
+    #[serde(default)]
+    pub actions: Vec<String>,
+    // Where a quick load put the player on this step
+    #[serde(default)]
+    pub load: Option<PlayerSnapshot>,
+    // Player config the walker was retuned with on this step
+    #[serde(default)]
+    pub tuning: Option<PlayerConfig>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Recording {
+    pub version: u32,
+    // Seconds per frame used when the recording is played back
+    pub timestep: f32,
+    // The player when the recording started. Playback starts from here
+    // instead of wherever the quick save or the config put the player.
+    #[serde(default)]
+    pub start: Option<PlayerSnapshot>,
+    // The player config when the recording started. Playback uses it
+    // instead of whatever `player.ron` says by then.
+    #[serde(default)]
+    pub tuning: Option<PlayerConfig>,
+    pub frames: Vec<InputFrame>
+}
+
+impl Default for Recording {
+    fn default() -> Recording {
+        Recording {
+            version: RECORDING_VERSION,
+            timestep: 1.0 / 60.0,
+            start: None,
+            tuning: None,
+            frames: Vec::new()
+        }
+    }
+}
+
+impl Recording {
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        if let Some(directory) = path.as_ref().parent() {
+            fs::create_dir_all(directory).map_err(ConfigError::File)?;
+        }
+        Config::write(self, path)
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        Recording::load_no_fallback(path)
+    }
+}
+
+/// Where the input of each frame comes from
+#[derive(Clone, Debug)]
+pub enum ReplayMode {
+    Live,
+    // Live input, written to the file when the game closes
+    Record(String),
+    // Input read from the file, one frame per update with a fixed timestep
+    Playback(String)
+}
+
+impl ReplayMode {
+    /// Reads `--record <file>` or `--replay <file>` from the command line
+    pub fn from_args() -> Self {
+        let mut args = env::args().skip(1);
+        let mut mode = ReplayMode::Live;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--record" => if let Some(path) = args.next() {
+                    mode = ReplayMode::Record(path);
+                },
+                "--replay" => if let Some(path) = args.next() {
+                    mode = ReplayMode::Playback(path);
+                },
+                _ => {}
+            }
+        }
+        mode
+    }
+}
+
+/// Tells the states when a played back recording has run out of frames
+#[derive(Default, Debug)]
+pub struct ReplayStatus {
+    pub finished: bool
+}
+
+/// Gameplay changes the states ask for between fixed steps. The
+/// `InputFrameSystem` moves them into the next `InputFrame`, so they get
+/// recorded and happen on the same step when played back.
+#[derive(Default, Debug)]
+pub struct PendingActions {
+    actions: Vec<String>,
+    load: Option<PlayerSnapshot>
+}
+
+impl PendingActions {
+    pub fn push(&mut self, action: &str) {
+        self.actions.push(action.to_string());
+    }
+
+    /// Puts the player where `snapshot` says on the next step
+    pub fn load(&mut self, snapshot: PlayerSnapshot) {
+        self.load = Some(snapshot);
+    }
+
+    pub fn take(&mut self) -> (Vec<String>, Option<PlayerSnapshot>) {
+        (mem::replace(&mut self.actions, Vec::new()), self.load.take())
+    }
+}
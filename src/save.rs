@@ -0,0 +1,207 @@
+use std::fs;
+use std::path::Path;
+
+use amethyst::config::{Config, ConfigError};
+use amethyst::core::cgmath::{Quaternion, Vector3};
+use amethyst::core::transform::Transform;
+use amethyst::ecs::prelude::Entity;
+use amethyst::prelude::*;
+
+use components::{InputState, PlayerComponent, PlayerState, Velocity};
+
+// Bump when the snapshot format changes
+pub const SAVE_VERSION: u32 = 2;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Config(ConfigError),
+    Io(::std::io::Error),
+    // The file was written by a newer or otherwise unknown format
+    UnsupportedVersion(u32),
+    MissingPlayer
+}
+
+/// Where the player was and what it was doing. Its tuning isn't part of
+/// it, that always comes from `resources/player.ron`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub state: PlayerState,
+    pub input_state: InputState,
+    pub translation: [f32; 3],
+    // (s, x, y, z)
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3]
+}
+
+impl PlayerSnapshot {
+    pub fn capture(player: &PlayerComponent, transform: &Transform) -> Self {
+        PlayerSnapshot {
+            state: player.state.clone(),
+            input_state: player.input_state.clone(),
+            translation: transform.translation.into(),
+            rotation: [
+                transform.rotation.s,
+                transform.rotation.v.x,
+                transform.rotation.v.y,
+                transform.rotation.v.z
+            ],
+            scale: transform.scale.into()
+        }
+    }
+
+    pub fn apply(&self, player: &mut PlayerComponent, transform: &mut Transform) {
+        player.state = self.state.clone();
+        player.input_state = self.input_state.clone();
+
+        let [s, x, y, z] = self.rotation;
+        transform.translation = Vector3::from(self.translation);
+        transform.rotation = Quaternion::new(s, x, y, z);
+        transform.scale = Vector3::from(self.scale);
+    }
+}
+
+/// Everything that is saved to disk
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub player: Option<PlayerSnapshot>
+}
+
+impl SaveGame {
+    /// Takes a snapshot of the player entity
+    pub fn capture(world: &World, player: Entity) -> Result<Self, SaveError> {
+        let players = world.read_storage::<PlayerComponent>();
+        let transforms = world.read_storage::<Transform>();
+
+        let (player, transform) = match (players.get(player), transforms.get(player)) {
+            (Some(player), Some(transform)) => (player, transform),
+            _ => return Err(SaveError::MissingPlayer)
+        };
+
+        Ok(SaveGame {
+            version: SAVE_VERSION,
+            player: Some(PlayerSnapshot::capture(player, transform))
+        })
+    }
+
+    /// Puts the saved values back into the player entity
+    pub fn restore(&self, world: &mut World, player: Entity) -> Result<(), SaveError> {
+        let snapshot = match self.player {
+            Some(ref snapshot) => snapshot,
+            None => return Err(SaveError::MissingPlayer)
+        };
+
+        let mut players = world.write_storage::<PlayerComponent>();
+        let mut transforms = world.write_storage::<Transform>();
+
+        // Whatever speed it had when saving is gone
+        if let Some(velocity) = world.write_storage::<Velocity>().get_mut(player) {
+            velocity.stop();
+        }
+
+        match (players.get_mut(player), transforms.get_mut(player)) {
+            (Some(player), Some(transform)) => {
+                snapshot.apply(player, transform);
+                Ok(())
+            }
+            _ => Err(SaveError::MissingPlayer)
+        }
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveError> {
+        if let Some(directory) = path.as_ref().parent() {
+            fs::create_dir_all(directory).map_err(SaveError::Io)?;
+        }
+        Config::write(self, path).map_err(SaveError::Config)
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, SaveError> {
+        let save = SaveGame::load_no_fallback(path).map_err(SaveError::Config)?;
+        if save.version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(save.version));
+        }
+        Ok(save)
+    }
+}
+
+/// Path of the quick save file
+pub fn quick_save_path() -> String {
+    format!("{}/saves/quicksave.ron", env!("CARGO_MANIFEST_DIR"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::path::PathBuf;
+
+    fn temp_save_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("state_walker_{}_{}.ron", name, ::std::process::id()))
+    }
+
+    fn world_with_player(player: PlayerComponent, transform: Transform) -> (World, Entity) {
+        let mut world = World::new();
+        world.register::<PlayerComponent>();
+        world.register::<Transform>();
+        world.register::<Velocity>();
+        let entity = world
+            .create_entity()
+            .with(player)
+            .with(transform)
+            .with(Velocity::default())
+            .build();
+        (world, entity)
+    }
+
+    #[test]
+    fn round_trip_through_a_file() {
+        let mut transform = Transform::default();
+        transform.translation = Vector3::new(120.0, 48.5, 0.0);
+        transform.scale = Vector3::new(2.0, 2.0, 1.0);
+        let player = PlayerComponent {
+            speed: 150.0,
+            state: PlayerState::Moving,
+            input_state: InputState::Keyboard,
+            ..PlayerComponent::default()
+        };
+        let (world, entity) = world_with_player(player, transform.clone());
+
+        let path = temp_save_path("round_trip");
+        SaveGame::capture(&world, entity).unwrap().write(&path).unwrap();
+        let save = SaveGame::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // Restore into a player that has moved on since
+        let (mut world, entity) = world_with_player(PlayerComponent::default(), Transform::default());
+        save.restore(&mut world, entity).unwrap();
+
+        let players = world.read_storage::<PlayerComponent>();
+        let transforms = world.read_storage::<Transform>();
+        let restored_player = players.get(entity).unwrap();
+        let restored_transform = transforms.get(entity).unwrap();
+        // Tuning isn't saved
+        assert_eq!(restored_player.speed, PlayerComponent::default().speed);
+        assert_eq!(restored_player.state, PlayerState::Moving);
+        assert_eq!(restored_player.input_state, InputState::Keyboard);
+        assert_eq!(restored_transform.translation, transform.translation);
+        assert_eq!(restored_transform.rotation, transform.rotation);
+        assert_eq!(restored_transform.scale, transform.scale);
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let path = temp_save_path("unknown_version");
+        let save = SaveGame {
+            version: SAVE_VERSION + 1,
+            player: None
+        };
+        save.write(&path).unwrap();
+        let result = SaveGame::read(&path);
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(SaveError::UnsupportedVersion(version)) => assert_eq!(version, SAVE_VERSION + 1),
+            other => panic!("expected an unsupported version, got {:?}", other)
+        }
+    }
+}
@@ -1,6 +1,11 @@
+use std::collections::HashMap;
+use std::fmt;
+
 use amethyst::assets::{AssetStorage, Loader};
 use amethyst::prelude::*;
 use amethyst::renderer::{PngFormat, Texture, TextureHandle};
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
 
 // https://github.com/amethyst/amethyst/blob/e99885926057e37e62dd27e88797a14e739ad136/examples/sprites/png_loader.rs
 pub fn load<N>(name: N, world: &World) -> TextureHandle
@@ -18,29 +23,140 @@ where
 }
 
 // https://github.com/amethyst/amethyst/blob/e99885926057e37e62dd27e88797a14e739ad136/examples/sprites/sprite.rs
-#[derive(Debug)]
+// Loaded from a `.ron` file with the same name as the texture
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SpriteSheetDefinition {
     pub sprite_w: f32,
     pub sprite_h: f32,
     pub row_count: usize,
     pub column_count: usize,
-    pub has_border: bool
+    #[serde(default)]
+    pub has_border: bool,
+    // Empty pixels around the whole grid
+    #[serde(default)]
+    pub margin: f32,
+    // Empty pixels between two sprites
+    #[serde(default)]
+    pub spacing: f32,
+    // Sprite number -> offset, for sprites not centered in their cell
+    #[serde(default)]
+    pub offsets: HashMap<usize, (f32, f32)>,
+    // Frame name -> sprite number
+    #[serde(default)]
+    pub frames: HashMap<String, usize>
 }
 
 impl SpriteSheetDefinition {
-    pub fn new(
-        sprite_w: f32,
-        sprite_h: f32,
-        row_count: usize,
-        column_count: usize,
-        has_border: bool
-    ) -> Self {
-        SpriteSheetDefinition {
-            sprite_w,
-            sprite_h,
-            row_count,
-            column_count,
-            has_border
+    pub fn sprite_count(&self) -> usize {
+        self.row_count * self.column_count
+    }
+
+    /// Sprite number `sprite` refers to, `None` for an unknown frame name
+    /// or a number past the last sprite
+    pub fn sprite_number(&self, sprite: &SpriteRef) -> Option<usize> {
+        let number = match *sprite {
+            SpriteRef::Number(number) => number,
+            SpriteRef::Name(ref name) => *self.frames.get(name)?
+        };
+        if number < self.sprite_count() {
+            Some(number)
+        } else {
+            None
+        }
+    }
+}
+
+/// A sprite in animation clips and tile maps, either its number in the
+/// sheet or the name of one of the sheet's `frames`: `3` or `"walk_3"`
+#[derive(PartialEq, Clone, Debug)]
+pub enum SpriteRef {
+    Number(usize),
+    Name(String)
+}
+
+impl Default for SpriteRef {
+    fn default() -> SpriteRef {
+        SpriteRef::Number(0)
+    }
+}
+
+impl fmt::Display for SpriteRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SpriteRef::Number(number) => write!(f, "{}", number),
+            SpriteRef::Name(ref name) => write!(f, "`{}`", name)
+        }
+    }
+}
+
+impl Serialize for SpriteRef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            SpriteRef::Number(number) => serializer.serialize_u64(number as u64),
+            SpriteRef::Name(ref name) => serializer.serialize_str(name)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SpriteRef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(SpriteRefVisitor)
+    }
+}
+
+struct SpriteRefVisitor;
+
+impl<'de> Visitor<'de> for SpriteRefVisitor {
+    type Value = SpriteRef;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a sprite number or a frame name")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<SpriteRef, E> {
+        Ok(SpriteRef::Number(value as usize))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<SpriteRef, E> {
+        if value < 0 {
+            return Err(E::invalid_value(de::Unexpected::Signed(value), &self));
+        }
+        Ok(SpriteRef::Number(value as usize))
+    }
+
+    // RON reads some numbers as floats
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<SpriteRef, E> {
+        if value < 0.0 || value.fract() != 0.0 {
+            return Err(E::invalid_value(de::Unexpected::Float(value), &self));
         }
+        Ok(SpriteRef::Number(value as usize))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<SpriteRef, E> {
+        Ok(SpriteRef::Name(value.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sprites_by_number_or_frame_name() {
+        let mut definition = SpriteSheetDefinition {
+            sprite_w: 64.0,
+            sprite_h: 64.0,
+            row_count: 1,
+            column_count: 6,
+            ..Default::default()
+        };
+        definition.frames.insert("walk_2".to_string(), 2);
+        definition.frames.insert("broken".to_string(), 9);
+
+        assert_eq!(definition.sprite_number(&SpriteRef::Number(5)), Some(5));
+        assert_eq!(definition.sprite_number(&SpriteRef::Name("walk_2".to_string())), Some(2));
+        assert_eq!(definition.sprite_number(&SpriteRef::Number(6)), None);
+        assert_eq!(definition.sprite_number(&SpriteRef::Name("jump".to_string())), None);
+        assert_eq!(definition.sprite_number(&SpriteRef::Name("broken".to_string())), None);
     }
-}
\ No newline at end of file
+}
@@ -6,17 +6,16 @@ use sprite;
 
 pub fn load(texture_id: u64, definition: &sprite::SpriteSheetDefinition) -> SpriteSheet {
     let mut sprites = Vec::with_capacity(definition.row_count * definition.column_count);
-    let (offset_w, offset_h) = offset_distances(&definition);
-    let (image_w, image_h) = (
-        offset_w * definition.column_count as f32,
-        offset_h * definition.row_count as f32
-    );
+    let (margin, spacing) = gaps(&definition);
+    let (image_w, image_h) = image_size(&definition);
+    let (offset_w, offset_h) = (definition.sprite_w + spacing, definition.sprite_h + spacing);
 
     for row in 0..definition.row_count {
         for col in 0..definition.column_count {
-            let offset_x = offset_w * col as f32;
-            let offset_y = offset_h * row as f32;
-            let sprite = create_sprite(
+            let offset_x = margin + offset_w * col as f32;
+            let offset_y = margin + offset_h * row as f32;
+            let sprite_number = row * definition.column_count + col;
+            let mut sprite = create_sprite(
                 image_w,
                 image_h,
                 definition.sprite_w,
@@ -25,7 +24,10 @@ pub fn load(texture_id: u64, definition: &sprite::SpriteSheetDefinition) -> Spri
                 offset_y
             );
 
-            let sprite_number = row * definition.column_count + col;
+            if let Some(&(x, y)) = definition.offsets.get(&sprite_number) {
+                sprite.offsets = [x, y];
+            }
+
             println!("{}: Sprite: {:?}", sprite_number, &sprite);
 
             sprites.push(sprite);
@@ -38,12 +40,30 @@ pub fn load(texture_id: u64, definition: &sprite::SpriteSheetDefinition) -> Spri
     }
 }
 
-fn offset_distances(definition: &sprite::SpriteSheetDefinition) -> (f32, f32) {
-    if definition.has_border {
-        (definition.sprite_w +1.0, definition.sprite_h +1.0)
-    } else {
-        (definition.sprite_w, definition.sprite_h)
-    }
+// Width of the border line, which is to the right of and below every
+// sprite, the first sprite starts right at the margin
+fn border(definition: &sprite::SpriteSheetDefinition) -> f32 {
+    if definition.has_border { 1.0 } else { 0.0 }
+}
+
+// Margin and spacing in pixels, the border of a sprite separates it from
+// the next one like spacing does
+fn gaps(definition: &sprite::SpriteSheetDefinition) -> (f32, f32) {
+    (definition.margin, definition.spacing + border(definition))
+}
+
+// Spacing only goes between two sprites, not after the last one, but the
+// last sprite still has its border
+fn image_size(definition: &sprite::SpriteSheetDefinition) -> (f32, f32) {
+    let (margin, spacing) = gaps(definition);
+    let border = border(definition);
+    let size = |sprite_size: f32, count: usize| {
+        sprite_size * count as f32 + spacing * (count as f32 - 1.0).max(0.0) + border + margin * 2.0
+    };
+    (
+        size(definition.sprite_w, definition.column_count),
+        size(definition.sprite_h, definition.row_count)
+    )
 }
 
 fn create_sprite(
@@ -72,4 +92,63 @@ fn create_sprite(
         offsets: [sprite_w / 2.0, sprite_h / 2.0],
         tex_coords
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(has_border: bool, margin: f32, spacing: f32) -> sprite::SpriteSheetDefinition {
+        sprite::SpriteSheetDefinition {
+            sprite_w: 32.0,
+            sprite_h: 16.0,
+            row_count: 2,
+            column_count: 3,
+            has_border,
+            margin,
+            spacing,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn spacing_only_between_sprites() {
+        // 3 * 32 + 2 * 4 + 2 * 2 wide, 2 * 16 + 1 * 4 + 2 * 2 high
+        assert_eq!(image_size(&definition(false, 2.0, 4.0)), (108.0, 40.0));
+        // One pixel right of and below every sprite
+        assert_eq!(image_size(&definition(true, 0.0, 0.0)), (99.0, 34.0));
+    }
+
+    #[test]
+    fn texture_coordinates_with_margin_and_spacing() {
+        let sheet = load(0, &definition(false, 2.0, 4.0));
+        let (image_w, image_h) = (108.0, 40.0);
+
+        // Last sprite of the second row ends right before the margin
+        let last = &sheet.sprites[5];
+        assert_eq!(last.tex_coords.left, (2.0 + 2.0 * 36.0) / image_w);
+        assert_eq!(last.tex_coords.right, (image_w - 2.0) / image_w);
+        assert_eq!(last.tex_coords.top, 1.0 - (2.0 + 20.0) / image_h);
+        assert_eq!(last.tex_coords.bottom, 1.0 - (image_h - 2.0) / image_h);
+
+        let first = &sheet.sprites[0];
+        assert_eq!(first.tex_coords.left, 2.0 / image_w);
+        assert_eq!(first.tex_coords.top, 1.0 - 2.0 / image_h);
+    }
+
+    #[test]
+    fn border_is_after_each_sprite() {
+        let sheet = load(0, &definition(true, 0.0, 0.0));
+        let (image_w, image_h) = (99.0, 34.0);
+
+        // The first sprite starts in the corner
+        let first = &sheet.sprites[0];
+        assert_eq!(first.tex_coords.left, 0.0);
+        assert_eq!(first.tex_coords.top, 1.0);
+
+        // The next one starts after the border pixel
+        let second = &sheet.sprites[1];
+        assert_eq!(second.tex_coords.left, 33.0 / image_w);
+        let below = &sheet.sprites[3];
+        assert_eq!(below.tex_coords.top, 1.0 - 17.0 / image_h);
+    }
+}
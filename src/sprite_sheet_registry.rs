@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use amethyst::assets::{AssetStorage, Loader};
+use amethyst::config::Config;
+use amethyst::ecs::prelude::Join;
+use amethyst::prelude::*;
+use amethyst::renderer::{MaterialTextureSet, SpriteRender, SpriteSheet, SpriteSheetHandle, SpriteSheetSet};
+
+use sprite;
+use sprite::SpriteSheetDefinition;
+use sprite_sheet_loader;
+
+pub struct SpriteSheetEntry {
+    pub index: u64,
+    pub handle: SpriteSheetHandle,
+    pub definition: SpriteSheetDefinition
+}
+
+/// Every sprite sheet found under `textures/`, keyed by the file name
+/// without the extension. `textures/walkingwhiteball.ron` describes the
+/// sheet in `textures/walkingwhiteball.png` and is registered as
+/// `walkingwhiteball`.
+#[derive(Default)]
+pub struct SpriteSheetRegistry {
+    sheets: HashMap<String, SpriteSheetEntry>
+}
+
+impl SpriteSheetRegistry {
+    pub fn get(&self, name: &str) -> Option<&SpriteSheetEntry> {
+        self.sheets.get(name)
+    }
+
+    /// Loads every definition in `directory` (relative to the project root)
+    /// and puts the registry into the world as a resource. Loading again
+    /// swaps the new sheets in on every entity drawn with the old ones.
+    pub fn load_all(world: &mut World, directory: &str) {
+        let path = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), directory);
+
+        let mut names = match fs::read_dir(&path) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().map_or(false, |ext| ext == "ron"))
+                .filter_map(|path| path.file_stem().and_then(|stem| stem.to_str()).map(String::from))
+                .collect::<Vec<String>>(),
+            Err(err) => {
+                println!("Failed to read sprite sheet directory `{}`: {}", path, err);
+                Vec::new()
+            }
+        };
+        // Sorted so every sheet gets the same index on every run
+        names.sort();
+
+        let mut registry = SpriteSheetRegistry::default();
+        for (index, name) in names.into_iter().enumerate() {
+            let definition_path = Path::new(&path).join(format!("{}.ron", name));
+            let definition = match SpriteSheetDefinition::load_no_fallback(&definition_path) {
+                Ok(definition) => definition,
+                Err(err) => {
+                    println!("Failed to load sprite sheet definition `{:?}`: {:?}", definition_path, err);
+                    continue;
+                }
+            };
+
+            let entry = register(world, directory, &name, index as u64, definition);
+            println!("Sprite sheet `{}` loaded with {} sprites", name, entry.definition.sprite_count());
+            registry.sheets.insert(name, entry);
+        }
+
+        let swaps = world.res.try_fetch::<SpriteSheetRegistry>().map_or_else(Vec::new, |old| {
+            old.sheets
+                .iter()
+                .filter_map(|(name, entry)| {
+                    registry.get(name).map(|new| (entry.handle.clone(), new.handle.clone()))
+                })
+                .collect::<Vec<(SpriteSheetHandle, SpriteSheetHandle)>>()
+        });
+        for sprite_render in (&mut world.write_storage::<SpriteRender>()).join() {
+            if let Some(&(_, ref new)) = swaps.iter().find(|&&(ref old, _)| *old == sprite_render.sprite_sheet) {
+                sprite_render.sprite_sheet = new.clone();
+            }
+        }
+
+        world.add_resource(registry);
+    }
+}
+
+fn register(
+    world: &mut World,
+    directory: &str,
+    name: &str,
+    index: u64,
+    definition: SpriteSheetDefinition
+) -> SpriteSheetEntry {
+    let texture = sprite::load(format!("{}/{}.png", directory, name), world);
+    world.write_resource::<MaterialTextureSet>().insert(index, texture);
+
+    let sprite_sheet = sprite_sheet_loader::load(index, &definition);
+    let handle = {
+        let loader = world.read_resource::<Loader>();
+        loader.load_from_data(
+            sprite_sheet,
+            (),
+            &world.read_resource::<AssetStorage<SpriteSheet>>()
+        )
+    };
+
+    world
+        .write_resource::<SpriteSheetSet>()
+        .insert(index, handle.clone());
+
+    SpriteSheetEntry {
+        index,
+        handle,
+        definition
+    }
+}
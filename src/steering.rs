@@ -0,0 +1,130 @@
+use amethyst::core::cgmath::{InnerSpace, Vector3};
+
+// Slowest speed inside the slowing radius, as a part of the full speed,
+// so the easing can't stall the entity right before the target
+const MIN_SPEED_FACTOR: f32 = 0.1;
+
+/// How the speed drops inside the slowing radius
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Easing {
+    // Full speed all the way
+    None,
+    // Speed drops in a straight line with the distance
+    Linear,
+    // Slows down gently at first and last (smoothstep)
+    Smooth
+}
+
+impl Easing {
+    // Speed factor for `t` = remaining distance / slowing radius
+    fn apply(&self, t: f32) -> f32 {
+        match *self {
+            Easing::None => 1.0,
+            Easing::Linear => t,
+            Easing::Smooth => t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Arrive {
+    pub speed: f32,
+    // Distance from the target where slowing down starts, 0.0 for never
+    pub slowing_radius: f32,
+    pub easing: Easing,
+    // Close enough to count as arrived
+    pub tolerance: f32
+}
+
+impl Arrive {
+    /// Moves `position` towards `target` for `delta_seconds`. Never moves
+    /// past the target, no matter how long the step. Returns the new
+    /// position and whether the target was reached.
+    pub fn step(&self, position: Vector3<f32>, target: Vector3<f32>, delta_seconds: f32) -> (Vector3<f32>, bool) {
+        let offset = target - position;
+        let distance = offset.magnitude();
+        if distance <= self.tolerance {
+            return (target, true);
+        }
+
+        let mut speed = self.speed;
+        if self.slowing_radius > 0.0 && distance < self.slowing_radius {
+            let factor = self.easing.apply(distance / self.slowing_radius);
+            speed *= factor.max(MIN_SPEED_FACTOR);
+        }
+
+        // Clamp to the remaining distance so a long frame can't overshoot
+        let step = (speed * delta_seconds).min(distance);
+        let position = position + offset / distance * step;
+
+        if distance - step <= self.tolerance {
+            (target, true)
+        } else {
+            (position, false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arrive(slowing_radius: f32, easing: Easing) -> Arrive {
+        Arrive {
+            speed: 100.0,
+            slowing_radius,
+            easing,
+            tolerance: 1.0
+        }
+    }
+
+    #[test]
+    fn huge_step_lands_on_the_target() {
+        let target = Vector3::new(300.0, 200.0, 0.0);
+        for easing in &[Easing::None, Easing::Linear, Easing::Smooth] {
+            let (position, arrived) = arrive(50.0, *easing).step(Vector3::new(0.0, 0.0, 0.0), target, 1000.0);
+            assert!(arrived);
+            assert_eq!(position, target);
+        }
+    }
+
+    #[test]
+    fn never_passes_the_target() {
+        let start = Vector3::new(0.0, 0.0, 0.0);
+        let target = Vector3::new(10.0, 0.0, 0.0);
+        // A bit more than the distance in one step
+        let (position, arrived) = arrive(0.0, Easing::None).step(start, target, 0.12);
+        assert!(arrived);
+        assert_eq!(position, target);
+
+        for delta_seconds in &[0.05, 0.099, 0.5, 3.0, 1.0e6] {
+            let (position, _) = arrive(0.0, Easing::None).step(start, target, *delta_seconds);
+            assert!(position.x <= target.x, "passed the target with {}: {:?}", delta_seconds, position);
+        }
+    }
+
+    #[test]
+    fn distance_shrinks_inside_the_slowing_radius() {
+        let target = Vector3::new(0.0, 0.0, 0.0);
+        for easing in &[Easing::Linear, Easing::Smooth] {
+            let steering = arrive(80.0, *easing);
+            let mut position = Vector3::new(100.0, 40.0, 0.0);
+            let mut distance = (target - position).magnitude();
+
+            let mut arrived = false;
+            for _ in 0..1000 {
+                let (next, done) = steering.step(position, target, 1.0 / 60.0);
+                let next_distance = (target - next).magnitude();
+                assert!(next_distance < distance, "{:?} stalled at {}", easing, distance);
+                position = next;
+                distance = next_distance;
+                if done {
+                    arrived = true;
+                    break;
+                }
+            }
+            assert!(arrived, "{:?} never arrived", easing);
+            assert_eq!(position, target);
+        }
+    }
+}
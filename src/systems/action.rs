@@ -0,0 +1,86 @@
+use amethyst::ecs::prelude::{Read, System, Write};
+use amethyst::input::InputHandler;
+use bindings::{PressedActions, ACTIONS};
+use gamepad::{GamepadConfig, GamepadState};
+
+/// Turns the bindings in `resources/input.ron` and the gamepad buttons in
+/// `resources/gamepad.ron` into `PressedActions`, so the states don't need
+/// to know which device pressed what
+#[derive(Default)]
+pub struct ActionSystem {
+    // Actions held down on the previous update
+    down: Vec<&'static str>
+}
+
+impl<'s> System<'s> for ActionSystem {
+    type SystemData = (
+        Read<'s, InputHandler<String, String>>,
+        Read<'s, GamepadState>,
+        Read<'s, GamepadConfig>,
+        Write<'s, PressedActions>
+    );
+
+    fn run(&mut self, (input, gamepads, gamepad_config, mut pressed): Self::SystemData) {
+        let down = ACTIONS
+            .iter()
+            .cloned()
+            .filter(|action| {
+                input.action_is_down(*action).unwrap_or(false)
+                    || gamepads.action_is_down(&gamepad_config, action)
+            })
+            .collect::<Vec<&'static str>>();
+
+        for action in down.iter() {
+            if !self.down.contains(action) {
+                pressed.press(action);
+            }
+        }
+        self.down = down;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::ecs::prelude::{RunNow, World};
+    use gamepad::{GamepadButton, GamepadEvent, SimulatedGamepad};
+    use systems::GamepadSystem;
+
+    // Runs the gamepad and action systems once with `events` coming from
+    // a simulated pad, returning the actions pressed by them
+    fn press(world: &World, action_system: &mut ActionSystem, events: &[GamepadEvent]) -> Vec<&'static str> {
+        let mut source = SimulatedGamepad::new();
+        for event in events {
+            source.push(event.clone());
+        }
+        GamepadSystem::new(Box::new(source)).run_now(&world.res);
+        action_system.run_now(&world.res);
+        world.write_resource::<PressedActions>().take()
+    }
+
+    #[test]
+    fn gamepad_buttons_press_actions_once() {
+        let mut gamepad_config = GamepadConfig::default();
+        gamepad_config.buttons.insert("pause".to_string(), GamepadButton::Start);
+
+        let mut world = World::new();
+        world.add_resource(InputHandler::<String, String>::new());
+        world.add_resource(GamepadState::default());
+        world.add_resource(gamepad_config);
+        world.add_resource(PressedActions::default());
+        let mut action_system = ActionSystem::default();
+
+        let pressed = press(
+            &world,
+            &mut action_system,
+            &[GamepadEvent::Connected(0), GamepadEvent::ButtonPressed(0, GamepadButton::Start)]
+        );
+        assert_eq!(pressed, vec!["pause"]);
+        // Still held down
+        assert!(press(&world, &mut action_system, &[]).is_empty());
+
+        press(&world, &mut action_system, &[GamepadEvent::ButtonReleased(0, GamepadButton::Start)]);
+        let pressed = press(&world, &mut action_system, &[GamepadEvent::ButtonPressed(0, GamepadButton::Start)]);
+        assert_eq!(pressed, vec!["pause"]);
+    }
+}
@@ -0,0 +1,86 @@
+use amethyst::animation::{AnimationCommand, AnimationControl, AnimationControlSet, ControlState};
+use amethyst::core::cgmath::MetricSpace;
+use amethyst::core::timing::Time;
+use amethyst::core::transform::Transform;
+use amethyst::ecs::prelude::{Join, Read, ReadStorage, System, WriteStorage};
+use amethyst::renderer::SpriteRender;
+use components::{AnimationController, Facing, PlayerComponent};
+
+// Limits for the playback rate of speed scaled clips
+const MIN_RATE: f32 = 0.25;
+const MAX_RATE: f32 = 2.0;
+
+/// Switches the animation clip when the player state or facing changes.
+/// Uses a direction specific clip when there is one, otherwise flips
+/// the sprite horizontally when facing left.
+pub struct AnimationControllerSystem;
+
+impl<'s> System<'s> for AnimationControllerSystem {
+    type SystemData = (
+        ReadStorage<'s, PlayerComponent>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Facing>,
+        WriteStorage<'s, AnimationController>,
+        WriteStorage<'s, AnimationControlSet<u32, SpriteRender>>,
+        WriteStorage<'s, SpriteRender>,
+        Read<'s, Time>
+    );
+
+    fn run(
+        &mut self,
+        (players, transforms, facings, mut controllers, mut control_sets, mut sprite_renders, time): Self::SystemData
+    ) {
+        for (player, transform, facing, controller, control_set, sprite_render) in (
+            &players,
+            &transforms,
+            facings.maybe(),
+            &mut controllers,
+            &mut control_sets,
+            &mut sprite_renders
+        ).join()
+        {
+            // How fast the entity actually moved since the last frame
+            let moved = controller
+                .last_position
+                .map_or(0.0, |last| last.distance(transform.translation));
+            controller.last_position = Some(transform.translation);
+
+            let directional_clip = facing.and_then(|facing| {
+                controller.directional_clip_for(&player.state, facing.direction).cloned()
+            });
+
+            // Flipping is only needed when there is no clip for the direction
+            sprite_render.flip_horizontal = directional_clip.is_none()
+                && facing.map_or(false, |facing| facing.direction.is_left());
+
+            let clip = match directional_clip.or_else(|| controller.clip_for(&player.state).cloned()) {
+                Some(clip) => clip,
+                None => continue
+            };
+
+            // Swap the old clip out only when the state maps to another clip
+            if controller.current != Some(clip.id) {
+                if let Some(current) = controller.current {
+                    control_set.abort(current);
+                }
+
+                control_set.insert(
+                    clip.id,
+                    AnimationControl::new(
+                        clip.handle.clone(),
+                        clip.loop_mode.end_control(),
+                        ControlState::Requested,
+                        AnimationCommand::Start,
+                        1.0
+                    )
+                );
+                controller.current = Some(clip.id);
+            }
+
+            if clip.speed_scaled && player.speed > 0.0 && time.delta_seconds() > 0.0 {
+                let rate = moved / time.delta_seconds() / player.speed;
+                control_set.set_rate(clip.id, rate.max(MIN_RATE).min(MAX_RATE));
+            }
+        }
+    }
+}
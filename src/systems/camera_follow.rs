@@ -0,0 +1,96 @@
+use amethyst::core::cgmath::Vector2;
+use amethyst::core::timing::Time;
+use amethyst::core::transform::Transform;
+use amethyst::ecs::prelude::{Join, Read, ReadExpect, ReadStorage, System, WriteStorage};
+use amethyst::renderer::{Camera, ScreenDimensions};
+use camera::View;
+use components::{CameraFollow, PlayerComponent};
+use tilemap::TileMap;
+
+/// Moves cameras with `CameraFollow` after the player, once the player
+/// leaves the deadzone in the middle of the view. Also rebuilds the
+/// projection when the window is resized or the zoom changes, so the
+/// world isn't stretched.
+pub struct CameraFollowSystem;
+
+impl<'s> System<'s> for CameraFollowSystem {
+    type SystemData = (
+        WriteStorage<'s, CameraFollow>,
+        WriteStorage<'s, Camera>,
+        WriteStorage<'s, Transform>,
+        ReadStorage<'s, PlayerComponent>,
+        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, TileMap>,
+        Read<'s, Time>
+    );
+
+    fn run(
+        &mut self,
+        (mut follows, mut cameras, mut transforms, players, screen, tile_map, time): Self::SystemData
+    ) {
+        let player_position = match (&players, &transforms).join().next() {
+            Some((_, transform)) => transform.translation.truncate(),
+            None => return
+        };
+
+        for (follow, camera, transform) in (&mut follows, &mut cameras, &mut transforms).join() {
+            let view = View::of_camera(transform, follow, &screen);
+
+            // Snaps to the player on the first update instead of panning
+            // over from the origin
+            let first_update = follow.projection_size.is_none();
+            if follow.projection_size != Some((view.size.x, view.size.y)) {
+                *camera = view.projection();
+                follow.projection_size = Some((view.size.x, view.size.y));
+            }
+
+            let center = view.corner + view.size / 2.0;
+            let half_deadzone = Vector2::new(follow.deadzone.0, follow.deadzone.1) / 2.0;
+            let wanted = Vector2::new(
+                follow_axis(center.x, player_position.x, half_deadzone.x),
+                follow_axis(center.y, player_position.y, half_deadzone.y)
+            );
+
+            let catch_up = if first_update || follow.smoothing <= 0.0 {
+                1.0
+            } else {
+                (follow.smoothing * time.delta_seconds()).min(1.0)
+            };
+            let mut center = center + (wanted - center) * catch_up;
+
+            if follow.clamp_to_world && tile_map.width > 0 {
+                let world = Vector2::new(
+                    tile_map.width as f32 * tile_map.tile_w,
+                    tile_map.height as f32 * tile_map.tile_h
+                );
+                center.x = clamp_axis(center.x, view.size.x, world.x);
+                center.y = clamp_axis(center.y, view.size.y, world.y);
+            }
+
+            let corner = center - view.size / 2.0;
+            transform.translation.x = corner.x;
+            transform.translation.y = corner.y;
+        }
+    }
+}
+
+// Where the middle of the view has to be for `position` to be inside the deadzone
+fn follow_axis(center: f32, position: f32, half_deadzone: f32) -> f32 {
+    if position > center + half_deadzone {
+        position - half_deadzone
+    } else if position < center - half_deadzone {
+        position + half_deadzone
+    } else {
+        center
+    }
+}
+
+// Keeps a view `view` wide inside a world `world` wide, or centers it on a
+// smaller world
+fn clamp_axis(center: f32, view: f32, world: f32) -> f32 {
+    if view >= world {
+        world / 2.0
+    } else {
+        center.max(view / 2.0).min(world - view / 2.0)
+    }
+}
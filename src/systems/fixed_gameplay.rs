@@ -0,0 +1,115 @@
+use amethyst::core::bundle::{Result, SystemBundle};
+use amethyst::ecs::prelude::DispatcherBuilder;
+use replay::ReplayMode;
+use systems::{
+    FixedStepBeginSystem, FixedStepEndSystem, InputFrameSystem, MovePlayerSystem, MoveToTargetSystem,
+    PhysicsSystem, PlayerActionSystem, ScreenBoundsSystem
+};
+
+/// The gameplay simulation, dispatched once per fixed step. Everything in
+/// it reads the input from one `InputFrame` per step, which is what makes
+/// played back recordings end up where they were recorded.
+pub struct FixedGameplayBundle {
+    replay: ReplayMode
+}
+
+impl FixedGameplayBundle {
+    pub fn new(replay: ReplayMode) -> Self {
+        FixedGameplayBundle { replay }
+    }
+}
+
+impl<'a, 'b> SystemBundle<'a, 'b> for FixedGameplayBundle {
+    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<()> {
+        builder.add(FixedStepBeginSystem, "fixed_step_begin_system", &[]);
+        builder.add(InputFrameSystem::new(self.replay), "input_frame_system", &["fixed_step_begin_system"]);
+        builder.add(PlayerActionSystem, "player_action_system", &["input_frame_system"]);
+        builder.add(MovePlayerSystem::default(), "move_player_system", &["player_action_system"]);
+        builder.add(PhysicsSystem, "physics_system", &["move_player_system"]);
+        builder.add(MoveToTargetSystem, "move_to_target_system", &["physics_system"]);
+        builder.add(ScreenBoundsSystem, "screen_bounds_system", &["move_to_target_system"]);
+        builder.add(FixedStepEndSystem, "fixed_step_end_system", &["screen_bounds_system"]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::core::cgmath::Vector3;
+    use amethyst::core::transform::Transform;
+    use amethyst::prelude::*;
+    use amethyst::renderer::ScreenDimensions;
+    use components::{Interpolation, MoveTarget, PlayerComponent, Velocity, Waypoints};
+    use replay::ReplayStatus;
+
+    fn recording_path(name: &str) -> String {
+        format!("{}/resources/replays/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    // Plays the recording to its end and returns where the player is
+    // after each of the `checkpoints` frames and at the end
+    fn play_back(name: &str, checkpoints: &[usize]) -> Vec<Vector3<f32>> {
+        let mut world = World::new();
+        world.add_resource(ScreenDimensions::new(800, 600, 1.0));
+
+        let mut builder = DispatcherBuilder::new();
+        FixedGameplayBundle::new(ReplayMode::Playback(recording_path(name)))
+            .build(&mut builder)
+            .unwrap();
+        let mut dispatcher = builder.build();
+        dispatcher.setup(&mut world.res);
+
+        // Somewhere else than where the recording starts, it has to put
+        // the player there itself
+        let player = world
+            .create_entity()
+            .with(PlayerComponent::default())
+            .with(Transform::default())
+            .with(Interpolation::default())
+            .with(Waypoints::default())
+            .with(MoveTarget::default())
+            .with(Velocity::default())
+            .build();
+
+        let mut positions = Vec::new();
+        for frame in 0..10_000 {
+            if checkpoints.contains(&frame) {
+                positions.push(world.read_storage::<Transform>().get(player).unwrap().translation);
+            }
+            if world.read_resource::<ReplayStatus>().finished {
+                break;
+            }
+            dispatcher.dispatch(&world.res);
+            world.maintain();
+        }
+        assert!(world.read_resource::<ReplayStatus>().finished, "recording never finished");
+
+        positions.push(world.read_storage::<Transform>().get(player).unwrap().translation);
+        positions
+    }
+
+    #[test]
+    fn recording_ends_where_it_was_recorded() {
+        // Walks right with the keyboard, turns up with the stick, clicks
+        // to the left with the mouse and walks left with the keyboard
+        // again, with the acceleration and friction in the recording
+        let positions = play_back("walk.ron", &[60, 90, 130]);
+        let expected = [
+            Vector3::new(488.3333, 300.0, 0.0),
+            Vector3::new(505.1843, 333.1491, 0.0),
+            Vector3::new(450.0, 350.0, 0.0),
+            Vector3::new(411.6667, 350.0, 0.0)
+        ];
+        assert_eq!(positions.len(), expected.len());
+        for (n, (position, expected)) in positions.iter().zip(expected.iter()).enumerate() {
+            assert!(
+                (position.x - expected.x).abs() < 0.01 && (position.y - expected.y).abs() < 0.01,
+                "segment {}: expected {:?}, got {:?}",
+                n,
+                expected,
+                position
+            );
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use amethyst::ecs::prelude::{System, Write};
+use gamepad::{GamepadEvent, GamepadEventSource, GamepadState};
+
+/// Feeds the events from a gamepad event source into the `GamepadState` resource
+pub struct GamepadSystem {
+    source: Box<dyn GamepadEventSource>,
+    events: Vec<GamepadEvent>
+}
+
+impl GamepadSystem {
+    pub fn new(source: Box<dyn GamepadEventSource>) -> Self {
+        GamepadSystem {
+            source,
+            events: Vec::new()
+        }
+    }
+}
+
+impl<'s> System<'s> for GamepadSystem {
+    type SystemData = Write<'s, GamepadState>;
+
+    fn run(&mut self, mut gamepads: Self::SystemData) {
+        self.source.poll(&mut self.events);
+        for event in self.events.drain(..) {
+            gamepads.handle_event(&event);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use amethyst::core::transform::{GlobalTransform, Transform};
+use amethyst::ecs::prelude::{Join, Read, ReadExpect, ReadStorage, System, Write};
+use amethyst::input::InputHandler;
+use amethyst::renderer::{Camera, MouseButton, ScreenDimensions, VirtualKeyCode};
+use camera;
+use camera::View;
+use components::PlayerComponent;
+use gamepad::{GamepadConfig, GamepadState};
+use pauser::FixedStep;
+use player_config::PlayerConfig;
+use replay::{InputFrame, PendingActions, Recording, ReplayMode, ReplayStatus, RECORDING_VERSION};
+use save::PlayerSnapshot;
+
+/// Fills the `InputFrame` resource from the live input and the
+/// `PendingActions`, or from a recording, and records the live input when
+/// asked to. Meant for a fixed timestep group, one frame is recorded or
+/// played back per fixed step.
+pub struct InputFrameSystem {
+    mode: ReplayMode,
+    recording: Recording,
+    // Next frame to play back
+    cursor: usize,
+    // Player config in the last recorded frame
+    tuning: Option<PlayerConfig>
+}
+
+impl InputFrameSystem {
+    pub fn new(mode: ReplayMode) -> Self {
+        let recording = match mode {
+            ReplayMode::Playback(ref path) => match Recording::read(path) {
+                Ok(ref recording) if recording.version != RECORDING_VERSION => {
+                    println!(
+                        "Recording `{}` has version {}, only version {} can be played back",
+                        path, recording.version, RECORDING_VERSION
+                    );
+                    Recording::default()
+                }
+                Ok(recording) => {
+                    println!("Playing back {} frames from `{}`", recording.frames.len(), path);
+                    recording
+                }
+                Err(err) => {
+                    println!("Failed to read the recording `{}`: {:?}", path, err);
+                    Recording::default()
+                }
+            },
+            _ => Recording::default()
+        };
+
+        InputFrameSystem {
+            mode,
+            recording,
+            cursor: 0,
+            tuning: None
+        }
+    }
+}
+
+impl<'s> System<'s> for InputFrameSystem {
+    type SystemData = (
+        Read<'s, InputHandler<String, String>>,
+        Read<'s, FixedStep>,
+        Read<'s, GamepadState>,
+        Read<'s, GamepadConfig>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, GlobalTransform>,
+        ReadStorage<'s, PlayerComponent>,
+        ReadStorage<'s, Transform>,
+        Read<'s, PlayerConfig>,
+        ReadExpect<'s, ScreenDimensions>,
+        Write<'s, PendingActions>,
+        Write<'s, InputFrame>,
+        Write<'s, ReplayStatus>
+    );
+
+    fn run(
+        &mut self,
+        (input, fixed_step, gamepads, gamepad_config, cameras, global_transforms, players, transforms, config, screen, mut pending, mut frame, mut status): Self::SystemData
+    ) {
+        let (actions, load) = pending.take();
+
+        if let ReplayMode::Playback(_) = self.mode {
+            // Only the recorded actions count, the live ones are dropped
+            match self.recording.frames.get(self.cursor) {
+                Some(recorded) => {
+                    *frame = recorded.clone();
+                    frame.delta_seconds = self.recording.timestep;
+                    if self.cursor == 0 {
+                        if frame.load.is_none() {
+                            frame.load = self.recording.start.clone();
+                        }
+                        if frame.tuning.is_none() {
+                            frame.tuning = self.recording.tuning.clone();
+                        }
+                    }
+                    self.cursor += 1;
+                }
+                None => {
+                    *frame = InputFrame::default();
+                    status.finished = true;
+                }
+            }
+            return;
+        }
+
+        let gamepad_movement = gamepads.movement(&gamepad_config);
+        let mouse_position = input.mouse_position().map(|position| {
+            // Without a camera the world is drawn like through an unmoved one
+            let world = match (&cameras, &global_transforms).join().next() {
+                Some((camera, transform)) => camera::screen_to_world(position, camera, transform, &screen),
+                None => camera::screen_to_world(
+                    position,
+                    &View::screen(&screen).projection(),
+                    &GlobalTransform::default(),
+                    &screen
+                )
+            };
+            (world.x, world.y)
+        });
+
+        *frame = InputFrame {
+            delta_seconds: fixed_step.timestep,
+            horizontal_movement: input.axis_value("horizontal_movement").unwrap_or(0.0),
+            vertical_movement: input.axis_value("vertical_movement").unwrap_or(0.0),
+            mouse_position,
+            left_mouse_down: input.mouse_button_is_down(MouseButton::Left),
+            right_mouse_down: input.mouse_button_is_down(MouseButton::Right),
+            shift_down: input.key_is_down(VirtualKeyCode::LShift) || input.key_is_down(VirtualKeyCode::RShift),
+            gamepad_movement: (gamepad_movement.x, gamepad_movement.y),
+            actions,
+            load,
+            tuning: None
+        };
+
+        if let ReplayMode::Record(_) = self.mode {
+            if self.recording.frames.is_empty() {
+                self.recording.start = (&players, &transforms)
+                    .join()
+                    .next()
+                    .map(|(player, transform)| PlayerSnapshot::capture(player, transform));
+                self.recording.tuning = Some(config.clone());
+            } else if self.tuning.as_ref() != Some(&*config) {
+                // `player.ron` was reloaded while recording
+                frame.tuning = Some(config.clone());
+            }
+            self.tuning = Some(config.clone());
+            self.recording.timestep = fixed_step.timestep;
+            self.recording.frames.push(frame.clone());
+        }
+    }
+}
+
+impl Drop for InputFrameSystem {
+    // Recordings are written out when the game closes
+    fn drop(&mut self) {
+        if let ReplayMode::Record(ref path) = self.mode {
+            match self.recording.write(path) {
+                Ok(()) => println!("Recorded {} frames to `{}`", self.recording.frames.len(), path),
+                Err(err) => println!("Failed to write the recording `{}`: {:?}", path, err)
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use amethyst::core::cgmath::VectorSpace;
+use amethyst::core::transform::Transform;
+use amethyst::ecs::prelude::{Join, Read, ReadStorage, System, WriteStorage};
+use components::Interpolation;
+use pauser::FixedStep;
+
+/// First system of a fixed step, puts the simulated translation back
+/// into the `Transform` in place of the interpolated one
+pub struct FixedStepBeginSystem;
+
+impl<'s> System<'s> for FixedStepBeginSystem {
+    type SystemData = (
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Interpolation>
+    );
+
+    fn run(&mut self, (mut transforms, mut interpolations): Self::SystemData) {
+        for (transform, interpolation) in (&mut transforms, &mut interpolations).join() {
+            // Something other than the interpolation moved the entity,
+            // e.g. loading a save, so take the new translation as it is
+            if interpolation.rendered != Some(transform.translation) {
+                interpolation.current = transform.translation;
+            }
+
+            transform.translation = interpolation.current;
+            interpolation.previous = interpolation.current;
+            interpolation.rendered = Some(interpolation.current);
+        }
+    }
+}
+
+/// Last system of a fixed step, remembers where the step moved the entity
+pub struct FixedStepEndSystem;
+
+impl<'s> System<'s> for FixedStepEndSystem {
+    type SystemData = (
+        ReadStorage<'s, Transform>,
+        WriteStorage<'s, Interpolation>
+    );
+
+    fn run(&mut self, (transforms, mut interpolations): Self::SystemData) {
+        for (transform, interpolation) in (&transforms, &mut interpolations).join() {
+            interpolation.current = transform.translation;
+        }
+    }
+}
+
+/// Runs once per frame after the fixed steps and moves the `Transform`
+/// between the last two simulated translations
+pub struct InterpolationSystem;
+
+impl<'s> System<'s> for InterpolationSystem {
+    type SystemData = (
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Interpolation>,
+        Read<'s, FixedStep>
+    );
+
+    fn run(&mut self, (mut transforms, mut interpolations, fixed_step): Self::SystemData) {
+        for (transform, interpolation) in (&mut transforms, &mut interpolations).join() {
+            if interpolation.rendered.is_none() {
+                continue;
+            }
+
+            // Moved since the last frame without a fixed step in between,
+            // e.g. restarting, so draw it right where it is now instead of
+            // putting it back between the old translations
+            if interpolation.rendered != Some(transform.translation) {
+                interpolation.previous = transform.translation;
+                interpolation.current = transform.translation;
+            }
+
+            transform.translation = interpolation
+                .previous
+                .lerp(interpolation.current, fixed_step.alpha);
+            interpolation.rendered = Some(transform.translation);
+        }
+    }
+}
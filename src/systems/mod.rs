@@ -1,3 +1,27 @@
+mod action;
+mod animation_controller;
+mod camera_follow;
+mod fixed_gameplay;
+mod gamepad;
+mod input_frame;
+mod interpolation;
 mod move_player;
+mod move_to_target;
+mod physics;
+mod player_actions;
+mod player_config;
+mod screen_bounds;
 
-pub use self::move_player::MovePlayerSystem;
\ No newline at end of file
+pub use self::action::ActionSystem;
+pub use self::animation_controller::AnimationControllerSystem;
+pub use self::camera_follow::CameraFollowSystem;
+pub use self::fixed_gameplay::FixedGameplayBundle;
+pub use self::gamepad::GamepadSystem;
+pub use self::input_frame::InputFrameSystem;
+pub use self::interpolation::{FixedStepBeginSystem, FixedStepEndSystem, InterpolationSystem};
+pub use self::move_player::MovePlayerSystem;
+pub use self::move_to_target::MoveToTargetSystem;
+pub use self::physics::PhysicsSystem;
+pub use self::player_actions::PlayerActionSystem;
+pub use self::player_config::PlayerConfigSystem;
+pub use self::screen_bounds::ScreenBoundsSystem;
@@ -1,90 +1,119 @@
-use amethyst::core::timing::Time;
-use amethyst::core::transform::Transform;
-use amethyst::ecs::prelude::{Join, Read, ReadExpect, System, WriteStorage};
-use amethyst::input::InputHandler;
-use amethyst::core::cgmath::{Vector2, Vector3, MetricSpace, InnerSpace};
-use amethyst::renderer::{ScreenDimensions, MouseButton};
+use amethyst::ecs::prelude::{Join, Read, System, WriteStorage};
+use amethyst::core::cgmath::{InnerSpace, Vector2, Vector3};
+use components::Facing;
+use components::MoveTarget;
 use components::PlayerComponent;
 use components::PlayerState;
+use components::Velocity;
+use components::Waypoints;
+use replay::InputFrame;
 
 #[derive(Default)]
-pub struct MovePlayerSystem {
-    mouse_target_location: Option<(f64, f64)>
-}
+pub struct MovePlayerSystem;
 
 impl<'s> System<'s> for MovePlayerSystem {
     type SystemData = (
         WriteStorage<'s, PlayerComponent>,
-        WriteStorage<'s, Transform>,
-        Read<'s, Time>,
-        Read<'s, InputHandler<String, String>>,
-        ReadExpect<'s, ScreenDimensions>
+        WriteStorage<'s, Velocity>,
+        WriteStorage<'s, Facing>,
+        WriteStorage<'s, Waypoints>,
+        WriteStorage<'s, MoveTarget>,
+        // Live or played back input, see `InputFrameSystem`
+        Read<'s, InputFrame>
     );
 
-    fn run(&mut self, (mut players, mut transforms, time, input, screen): Self::SystemData) {
-        for (player, transform) in (&mut players, &mut transforms).join() {
-            // Handle player movement with keyboard
-            if player.uses_keyboard() {
-                let mut player_location = transform.translation;
-                let mut input_dir = Vector2::new(0.0, 0.0);
-
-                input_dir.x = input.axis_value("horizontal_movement").unwrap();
-                input_dir.y = input.axis_value("vertical_movement").unwrap();
+    fn run(
+        &mut self,
+        (mut players, mut velocities, mut facings, mut waypoints, mut move_targets, input): Self::SystemData
+    ) {
+        let delta_seconds = input.delta_seconds;
 
-                // Actually move the player entity
-                transform.translation[0] += player.speed * time.delta_seconds() * input_dir.x as f32;
-                transform.translation[1] += player.speed * time.delta_seconds() * input_dir.y as f32;
+        // Every player keeps its own velocity, path and target, but they all
+        // read the same input frame and the one active gamepad, each the
+        // part of it its input mode picks
+        for (player, velocity, mut facing, waypoints, move_target) in (
+            &mut players,
+            (&mut velocities).maybe(),
+            (&mut facings).maybe(),
+            (&mut waypoints).maybe(),
+            (&mut move_targets).maybe()
+        ).join()
+        {
+            // Keyboard and gamepad only pick the direction, the `PhysicsSystem`
+            // moves the player by the resulting velocity
+            let direction = if player.uses_keyboard() {
+                let keys = Vector2::new(input.horizontal_movement as f32, input.vertical_movement as f32);
+                // Diagonals are as fast as straight lines
+                if keys.magnitude() > 1.0 {
+                    keys.normalize()
+                } else {
+                    keys
+                }
+            } else if player.uses_controller() {
+                Vector2::new(input.gamepad_movement.0, input.gamepad_movement.1)
+            } else {
+                Vector2::new(0.0, 0.0)
+            };
 
-                // Set player state to `Standing` or `Moving` only once
-                // when player entity starts moving or stops moving
-                if player_location == transform.translation && player.state != PlayerState::Standing {
-                    player.state = PlayerState::Standing;
-                    println!("PlayerState set to standing.");
-                } else if player_location != transform.translation && player.state != PlayerState::Moving {
-                    player.state = PlayerState::Moving;
-                    println!("PlayerState set to moving.");
+            if let Some(velocity) = velocity {
+                if player.uses_mouse() {
+                    // The `MoveToTargetSystem` does the walking instead
+                    velocity.stop();
+                } else {
+                    if let Some(ref mut facing) = facing {
+                        facing.look_towards(direction);
+                    }
+                    // The `PhysicsSystem` sets the state, once it knows
+                    // whether the player got anywhere
+                    velocity.linear = player.accelerate(velocity.linear, direction, delta_seconds);
                 }
             }
 
-            // Handle player movement with mouse clicks
+            // Handle player movement with mouse clicks, the walking itself
+            // is done by the `MoveToTargetSystem`
             if player.uses_mouse() {
-                if input.mouse_button_is_down(MouseButton::Left) {
-                    // Set new target location for the player entity to move to
-                    self.mouse_target_location = input.mouse_position();
+                let (waypoints, move_target) = match (waypoints, move_target) {
+                    (Some(waypoints), Some(move_target)) => (waypoints, move_target),
+                    _ => continue
+                };
+                let clicked = input.left_mouse_down && !waypoints.left_was_down;
+                waypoints.left_was_down = input.left_mouse_down;
+
+                if input.right_mouse_down {
+                    // Right click cancels the whole path
+                    waypoints.clear();
+                } else if let Some((x, y)) = input.mouse_position {
+                    // Already in world coordinates, see `InputFrameSystem`
+                    let location = Vector3::new(x, y, 0.0);
 
-                    // Let's do it only once per click
-                    if !player.is_moving() {
-                        player.state = PlayerState::Moving;
-                        println!("PlayerState set to moving.");
+                    if clicked && input.shift_down {
+                        // Shift+click queues the location after the others
+                        waypoints.push(location);
+                    } else if input.left_mouse_down && !input.shift_down {
+                        // A click sets a new target, holding the button
+                        // keeps following the cursor
+                        waypoints.set(location);
                     }
                 }
 
-                // Get x and y coordinates from the saved location
-                if let Some((pox, poy)) = self.mouse_target_location {
-                    // Move the player as long the player state is set as `Moving`
-                    if player.is_moving() {
-                        // Mouse target location into `Vector3`
-                        // Y-axis needs to be "inverted" with the screen height
-                        // for some reason ¯\_(ツ)_/¯
-                        let mut target_location = Vector3::new(pox as f32, screen.height() - poy as f32, 0.0);
-                        let distance = transform.translation.distance(target_location);
-                        let direction = (target_location - transform.translation).normalize();
+                move_target.steering = player.arrive();
+                move_target.target = waypoints.current();
 
-                        // Actually move the player entity
-                        transform.translation[0] += player.speed * time.delta_seconds() * direction.x as f32;
-                        transform.translation[1] += player.speed * time.delta_seconds() * direction.y as f32;
-
-                        // Distance stops at around 0.5 or so
-                        // So we conclude that we're there
-                        if distance <= 1.0 {
-                            self.mouse_target_location = None;
-                            player.state = PlayerState::Standing;
-                            println!("PlayerState set to standing.");
-                        }
-                    }
+                // Let's do it only once per path
+                if move_target.target.is_some() && !player.is_moving() {
+                    player.state = PlayerState::Moving;
+                    println!("PlayerState set to moving.");
+                } else if move_target.target.is_none() && player.is_moving() {
+                    player.state = PlayerState::Standing;
+                    println!("PlayerState set to standing.");
+                }
+            } else if let Some(waypoints) = waypoints {
+                // The path is dropped when switching to another input
+                waypoints.clear();
+                if let Some(move_target) = move_target {
+                    move_target.target = None;
                 }
             }
-
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,86 @@
+use amethyst::core::transform::Transform;
+use amethyst::ecs::prelude::{Join, Read, ReadStorage, System, WriteStorage};
+use components::{Collider, Facing, MoveTarget, PlayerComponent, PlayerState, Waypoints};
+use replay::InputFrame;
+use tilemap::TileMap;
+
+/// Walks every entity with a `MoveTarget` towards its target, following
+/// its `Waypoints` when it has them
+pub struct MoveToTargetSystem;
+
+impl<'s> System<'s> for MoveToTargetSystem {
+    type SystemData = (
+        WriteStorage<'s, MoveTarget>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Waypoints>,
+        WriteStorage<'s, Facing>,
+        WriteStorage<'s, PlayerComponent>,
+        ReadStorage<'s, Collider>,
+        // Same step length as the input, also when played back
+        Read<'s, InputFrame>,
+        Read<'s, TileMap>
+    );
+
+    fn run(
+        &mut self,
+        (mut move_targets, mut transforms, mut waypoints, mut facings, mut players, colliders, input, tile_map): Self::SystemData
+    ) {
+        for (move_target, transform, mut waypoints, mut facing, mut player, collider) in (
+            &mut move_targets,
+            &mut transforms,
+            (&mut waypoints).maybe(),
+            (&mut facings).maybe(),
+            (&mut players).maybe(),
+            colliders.maybe()
+        ).join()
+        {
+            if let Some(ref waypoints) = waypoints {
+                move_target.target = waypoints.current();
+            }
+
+            let target_location = match move_target.target {
+                Some(target_location) => target_location,
+                None => continue
+            };
+
+            if let Some(ref mut facing) = facing {
+                facing.look_towards((target_location - transform.translation).truncate());
+            }
+
+            // Actually move the entity, stopping right at the target
+            let (location, arrived) = move_target
+                .steering
+                .step(transform.translation, target_location, input.delta_seconds);
+            let movement = (location - transform.translation).truncate();
+            let moved_to = tile_map.move_entity(transform.translation, movement, collider);
+            // Walked into a wall with nowhere to slide, so give up the path
+            let stuck = moved_to == transform.translation;
+            transform.translation = moved_to;
+
+            if arrived {
+                move_target.target = match waypoints {
+                    Some(ref mut waypoints) => {
+                        waypoints.advance();
+                        waypoints.current()
+                    }
+                    None => None
+                };
+            } else if stuck {
+                if let Some(ref mut waypoints) = waypoints {
+                    waypoints.clear();
+                }
+                move_target.target = None;
+            } else {
+                continue;
+            }
+
+            // Players stop walking at the end of the path
+            if let Some(ref mut player) = player {
+                if move_target.target.is_none() && player.is_moving() {
+                    player.state = PlayerState::Standing;
+                    println!("PlayerState set to standing.");
+                }
+            }
+        }
+    }
+}
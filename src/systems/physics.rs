@@ -0,0 +1,74 @@
+use amethyst::core::transform::Transform;
+use amethyst::ecs::prelude::{Join, Read, ReadStorage, System, WriteStorage};
+use components::{Collider, PlayerComponent, PlayerState, Velocity};
+use replay::InputFrame;
+use tilemap::TileMap;
+
+// Rounding from moving in several steps, see `TileMap::move_box`, isn't
+// running into a wall
+const BLOCKED_EPSILON: f32 = 0.001;
+
+/// Moves every entity with a `Velocity` by it, sliding along the solid
+/// tiles of the map. Whatever part of the velocity runs into a wall is
+/// lost, so the entity doesn't keep pushing into it. Players walking with
+/// the keyboard or a gamepad are `Moving` only while they get anywhere.
+pub struct PhysicsSystem;
+
+impl<'s> System<'s> for PhysicsSystem {
+    type SystemData = (
+        WriteStorage<'s, Velocity>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, PlayerComponent>,
+        ReadStorage<'s, Collider>,
+        // Same step length as the input, also when played back
+        Read<'s, InputFrame>,
+        Read<'s, TileMap>
+    );
+
+    fn run(&mut self, (mut velocities, mut transforms, mut players, colliders, input, tile_map): Self::SystemData) {
+        for (velocity, transform, player, collider) in (
+            &mut velocities,
+            &mut transforms,
+            (&mut players).maybe(),
+            colliders.maybe()
+        ).join()
+        {
+            let start = transform.translation;
+
+            if velocity.is_moving() {
+                let movement = velocity.linear * input.delta_seconds;
+                let unblocked = transform.translation + movement.extend(0.0);
+                let moved_to = tile_map.move_entity(transform.translation, movement, collider);
+
+                // Stopped short by a wall on that axis
+                if (moved_to.x - unblocked.x).abs() > BLOCKED_EPSILON {
+                    velocity.linear.x = 0.0;
+                }
+                if (moved_to.y - unblocked.y).abs() > BLOCKED_EPSILON {
+                    velocity.linear.y = 0.0;
+                }
+                transform.translation = moved_to;
+            }
+
+            // Walking to the mouse target is up to the `MoveToTargetSystem`
+            let player = match player {
+                Some(player) => player,
+                None => continue
+            };
+            if player.uses_mouse() {
+                continue;
+            }
+
+            // Set player state to `Standing` or `Moving` only once
+            // when player entity starts moving or stops moving
+            let moved = transform.translation != start;
+            if !moved && player.state != PlayerState::Standing {
+                player.state = PlayerState::Standing;
+                println!("PlayerState set to standing.");
+            } else if moved && player.state != PlayerState::Moving {
+                player.state = PlayerState::Moving;
+                println!("PlayerState set to moving.");
+            }
+        }
+    }
+}
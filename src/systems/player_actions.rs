@@ -0,0 +1,113 @@
+use amethyst::core::transform::Transform;
+use amethyst::ecs::prelude::{Join, Read, System, Write, WriteStorage};
+use components::{Facing, Interpolation, MoveTarget, PlayerComponent, ScreenBounds, Velocity, Waypoints};
+use player_config::{PlayerConfig, PlayerSpawn};
+use replay::InputFrame;
+
+/// Applies the tuning, gameplay actions and quick load of the current
+/// `InputFrame` to every player, before anything moves in the step. The
+/// actions aren't tied to a player, so cycling the input mode cycles it
+/// for all of them.
+pub struct PlayerActionSystem;
+
+impl<'s> System<'s> for PlayerActionSystem {
+    type SystemData = (
+        WriteStorage<'s, PlayerComponent>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, ScreenBounds>,
+        WriteStorage<'s, Facing>,
+        WriteStorage<'s, Waypoints>,
+        WriteStorage<'s, MoveTarget>,
+        WriteStorage<'s, Velocity>,
+        WriteStorage<'s, Interpolation>,
+        Read<'s, InputFrame>,
+        Write<'s, PlayerConfig>,
+        Read<'s, PlayerSpawn>
+    );
+
+    fn run(
+        &mut self,
+        (mut players, mut transforms, mut screen_bounds, mut facings, mut waypoints, mut move_targets, mut velocities, mut interpolations, frame, mut config, spawn): Self::SystemData
+    ) {
+        if frame.actions.is_empty() && frame.load.is_none() && frame.tuning.is_none() {
+            return;
+        }
+
+        // Played back recordings bring their own tuning, see `InputFrameSystem`
+        if let Some(ref tuning) = frame.tuning {
+            *config = tuning.clone();
+        }
+
+        for (player, transform, mut bounds, mut facing, waypoints, move_target, velocity, interpolation) in (
+            &mut players,
+            &mut transforms,
+            (&mut screen_bounds).maybe(),
+            (&mut facings).maybe(),
+            (&mut waypoints).maybe(),
+            (&mut move_targets).maybe(),
+            (&mut velocities).maybe(),
+            (&mut interpolations).maybe()
+        ).join()
+        {
+            let mut teleported = false;
+
+            if frame.tuning.is_some() {
+                config.apply(player);
+                if let Some(ref mut facing) = facing {
+                    facing.mode = config.facing;
+                }
+            }
+
+            for action in frame.actions.iter() {
+                match action.as_str() {
+                    "cycle_input_mode" => {
+                        player.input_state = player.input_state.next();
+                        println!("Player input state set to: {:?}", player.input_state);
+                    }
+                    "cycle_bounds_mode" => if let Some(ref mut bounds) = bounds {
+                        bounds.mode = bounds.mode.next();
+                        println!("Player screen bounds set to: {:?}", bounds.mode);
+                    },
+                    "restart" => {
+                        *player = config.player();
+                        if let Some(ref spawn) = spawn.transform {
+                            *transform = spawn.clone();
+                        }
+                        // The spawn position may have been changed in the reloaded config
+                        if let Some((x, y)) = config.spawn_position {
+                            transform.translation.x = x;
+                            transform.translation.y = y;
+                        }
+                        if let Some(ref mut facing) = facing {
+                            **facing = config.facing();
+                        }
+                        teleported = true;
+                        println!("Gameplay restarted");
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some(ref snapshot) = frame.load {
+                snapshot.apply(player, transform);
+                teleported = true;
+            }
+
+            // Nothing from before carries over to the new position
+            if teleported {
+                if let Some(waypoints) = waypoints {
+                    waypoints.clear();
+                }
+                if let Some(move_target) = move_target {
+                    move_target.target = None;
+                }
+                if let Some(velocity) = velocity {
+                    velocity.stop();
+                }
+                if let Some(interpolation) = interpolation {
+                    interpolation.previous = transform.translation;
+                }
+            }
+        }
+    }
+}
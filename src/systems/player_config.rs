@@ -0,0 +1,66 @@
+use amethyst::config::Config;
+use amethyst::core::timing::Time;
+use amethyst::ecs::prelude::{Join, Read, System, Write, WriteStorage};
+use components::{Facing, PlayerComponent};
+use player_config::PlayerConfig;
+use watcher::FileWatcher;
+
+// Seconds between looking at the file
+const CHECK_INTERVAL: f32 = 0.5;
+
+/// Reloads the `PlayerConfig` when its file changes and retunes every
+/// player with it, so the walker can be tuned while the game runs
+pub struct PlayerConfigSystem {
+    path: String,
+    watcher: FileWatcher,
+    since_check: f32
+}
+
+impl PlayerConfigSystem {
+    pub fn new(path: String) -> Self {
+        let mut watcher = FileWatcher::new();
+        watcher.watch(&path);
+
+        PlayerConfigSystem {
+            path,
+            watcher,
+            since_check: 0.0
+        }
+    }
+}
+
+impl<'s> System<'s> for PlayerConfigSystem {
+    type SystemData = (
+        WriteStorage<'s, PlayerComponent>,
+        WriteStorage<'s, Facing>,
+        Write<'s, PlayerConfig>,
+        Read<'s, Time>
+    );
+
+    fn run(&mut self, (mut players, mut facings, mut config, time): Self::SystemData) {
+        self.since_check += time.delta_seconds();
+        if self.since_check < CHECK_INTERVAL {
+            return;
+        }
+        self.since_check = 0.0;
+
+        if self.watcher.changed().is_empty() {
+            return;
+        }
+
+        // A half written or broken file keeps the old tuning
+        match PlayerConfig::load_no_fallback(&self.path) {
+            Ok(reloaded) => {
+                for (player, facing) in (&mut players, (&mut facings).maybe()).join() {
+                    reloaded.apply(player);
+                    if let Some(facing) = facing {
+                        facing.mode = reloaded.facing;
+                    }
+                }
+                *config = reloaded;
+                println!("Player config reloaded from `{}`", self.path);
+            }
+            Err(err) => println!("Failed to reload the player config `{}`: {:?}", self.path, err)
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use amethyst::assets::AssetStorage;
+use amethyst::core::cgmath::Vector3;
+use amethyst::core::transform::Transform;
+use amethyst::ecs::prelude::{Join, Read, ReadExpect, ReadStorage, System, WriteStorage};
+use amethyst::renderer::{ScreenDimensions, SpriteRender, SpriteSheet};
+use camera::View;
+use components::{BoundsMode, CameraFollow, Collider, Interpolation, MoveTarget, ScreenBounds, Velocity, Waypoints};
+use tilemap::TileMap;
+
+/// Keeps entities with `ScreenBounds` in the camera view, or on the
+/// screen when there is no camera. The size of the entity comes from its
+/// sprite, or its collider when nothing is drawn. The view is read on
+/// every step so resizing the window or zooming moves the edges with it.
+/// Bouncing turns the entity around, and wrapping never leaves it inside
+/// a wall on the other side.
+pub struct ScreenBoundsSystem;
+
+impl<'s> System<'s> for ScreenBoundsSystem {
+    type SystemData = (
+        ReadStorage<'s, ScreenBounds>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Interpolation>,
+        WriteStorage<'s, MoveTarget>,
+        WriteStorage<'s, Waypoints>,
+        WriteStorage<'s, Velocity>,
+        ReadStorage<'s, SpriteRender>,
+        ReadStorage<'s, Collider>,
+        ReadStorage<'s, CameraFollow>,
+        Read<'s, AssetStorage<SpriteSheet>>,
+        Read<'s, TileMap>,
+        ReadExpect<'s, ScreenDimensions>
+    );
+
+    fn run(
+        &mut self,
+        (bounds, mut transforms, mut interpolations, mut move_targets, mut waypoints, mut velocities, sprite_renders, colliders, cameras, sprite_sheets, tile_map, screen): Self::SystemData
+    ) {
+        let view = (&cameras, &transforms)
+            .join()
+            .next()
+            .map_or_else(|| View::screen(&screen), |(camera, transform)| View::of_camera(transform, camera, &screen));
+
+        for (bounds, transform, interpolation, move_target, waypoints, velocity, sprite_render, collider) in (
+            &bounds,
+            &mut transforms,
+            (&mut interpolations).maybe(),
+            (&mut move_targets).maybe(),
+            (&mut waypoints).maybe(),
+            (&mut velocities).maybe(),
+            sprite_renders.maybe(),
+            colliders.maybe()
+        ).join()
+        {
+            let extents = sprite_render
+                .and_then(|sprite_render| sprite_extents(&sprite_sheets, sprite_render))
+                .or_else(|| collider.map(|collider| (
+                    (collider.half_extents.x, collider.half_extents.x),
+                    (collider.half_extents.y, collider.half_extents.y)
+                )))
+                .unwrap_or(((0.0, 0.0), (0.0, 0.0)));
+
+            let (mut translation, changed) = apply(bounds.mode, transform.translation, extents, view);
+            if !changed.0 && !changed.1 {
+                continue;
+            }
+            if bounds.mode == BoundsMode::Wrap {
+                if let Some(collider) = collider {
+                    translation = clear_of_walls(&tile_map, transform.translation, translation, changed, collider, view);
+                }
+            }
+            transform.translation = translation;
+
+            // Jumping to the other side shouldn't be drawn as a slide across
+            // the whole view
+            if bounds.mode == BoundsMode::Wrap {
+                if let Some(interpolation) = interpolation {
+                    interpolation.previous = translation;
+                }
+            }
+
+            if bounds.mode == BoundsMode::Bounce {
+                if let Some(velocity) = velocity {
+                    if changed.0 {
+                        velocity.linear.x = -velocity.linear.x;
+                    }
+                    if changed.1 {
+                        velocity.linear.y = -velocity.linear.y;
+                    }
+                }
+            }
+
+            // Targets past the edge would never be reached, so they get the
+            // same treatment as the entity. Bouncing also sends the rest of
+            // the path back the way it came.
+            let bound_target = |point: Vector3<f32>| {
+                let point = match bounds.mode {
+                    BoundsMode::Bounce => mirror(point, translation, changed),
+                    _ => point
+                };
+                apply(bounds.mode, point, extents, view).0
+            };
+            if let Some(move_target) = move_target {
+                if let Some(target) = move_target.target {
+                    move_target.target = Some(bound_target(target));
+                }
+            }
+            if let Some(waypoints) = waypoints {
+                for point in waypoints.points.iter_mut() {
+                    *point = bound_target(*point);
+                }
+            }
+        }
+    }
+}
+
+// Sprite pixels left and right, and below and above the translation
+fn sprite_extents(
+    sprite_sheets: &AssetStorage<SpriteSheet>,
+    sprite_render: &SpriteRender
+) -> Option<((f32, f32), (f32, f32))> {
+    let sprite_sheet = sprite_sheets.get(&sprite_render.sprite_sheet)?;
+    let sprite = sprite_sheet.sprites.get(sprite_render.sprite_number)?;
+    // Offsets shift the sprite left and down from the translation
+    Some((
+        (sprite.offsets[0], sprite.width - sprite.offsets[0]),
+        (sprite.offsets[1], sprite.height - sprite.offsets[1])
+    ))
+}
+
+// Also returns which of the x and y coordinates changed
+fn apply(
+    mode: BoundsMode,
+    point: Vector3<f32>,
+    (extents_x, extents_y): ((f32, f32), (f32, f32)),
+    view: View
+) -> (Vector3<f32>, (bool, bool)) {
+    let (x, changed_x) = mode.apply(point.x, extents_x, (view.corner.x, view.size.x));
+    let (y, changed_y) = mode.apply(point.y, extents_y, (view.corner.y, view.size.y));
+    (Vector3::new(x, y, point.z), (changed_x, changed_y))
+}
+
+// `point` on the other side of `center` along the axes that bounced
+fn mirror(point: Vector3<f32>, center: Vector3<f32>, (x, y): (bool, bool)) -> Vector3<f32> {
+    Vector3::new(
+        if x { 2.0 * center.x - point.x } else { point.x },
+        if y { 2.0 * center.y - point.y } else { point.y },
+        point.z
+    )
+}
+
+// Wrapping can land the entity in a wall on the other side, so it is
+// pushed further in one tile at a time. When the whole way across is
+// blocked it doesn't wrap and stays where it was.
+fn clear_of_walls(
+    tile_map: &TileMap,
+    from: Vector3<f32>,
+    wrapped: Vector3<f32>,
+    (changed_x, changed_y): (bool, bool),
+    collider: &Collider,
+    view: View
+) -> Vector3<f32> {
+    if !tile_map.overlaps_solid(wrapped, collider.half_extents) {
+        return wrapped;
+    }
+
+    // Inwards is the way the entity was going when it left
+    let step = Vector3::new(
+        if changed_x { (from.x - wrapped.x).signum() * tile_map.tile_w } else { 0.0 },
+        if changed_y { (from.y - wrapped.y).signum() * tile_map.tile_h } else { 0.0 },
+        0.0
+    );
+    let steps = (view.size.x / tile_map.tile_w).max(view.size.y / tile_map.tile_h).ceil() as usize;
+
+    let mut position = wrapped;
+    for _ in 0..steps {
+        position += step;
+        if !tile_map.overlaps_solid(position, collider.half_extents) {
+            return position;
+        }
+    }
+    from
+}
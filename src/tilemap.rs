@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+use amethyst::config::Config;
+use amethyst::core::cgmath::{Vector2, Vector3};
+use amethyst::core::transform::{GlobalTransform, Transform};
+use amethyst::ecs::prelude::Entity;
+use amethyst::prelude::*;
+use amethyst::renderer::SpriteRender;
+
+use components::Collider;
+use sprite::SpriteRef;
+use sprite_sheet_registry::SpriteSheetRegistry;
+
+// Gap left between an entity and the tile it bumped into
+const SKIN: f32 = 0.01;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TileDefinition {
+    // Sprite number or frame name in the tileset
+    pub sprite: SpriteRef,
+    #[serde(default)]
+    pub solid: bool
+}
+
+/// Loaded from `resources/map.ron`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TileMapDefinition {
+    // Name of the sprite sheet in the `SpriteSheetRegistry`
+    pub tileset: String,
+    pub tile_w: f32,
+    pub tile_h: f32,
+    // Character in `rows` -> tile, characters missing from here are empty
+    pub legend: HashMap<char, TileDefinition>,
+    // Top row first
+    pub rows: Vec<String>
+}
+
+/// Collision data of the loaded map. The bottom left corner of the map
+/// is at the world origin. An empty map (the default) blocks nothing.
+#[derive(Debug, Default)]
+pub struct TileMap {
+    pub width: usize,
+    pub height: usize,
+    pub tile_w: f32,
+    pub tile_h: f32,
+    // Bottom row first
+    solid: Vec<bool>,
+    // Sprite entity of every drawn tile
+    tiles: Vec<Entity>
+}
+
+impl TileMap {
+    pub fn from_definition(definition: &TileMapDefinition) -> Self {
+        let height = definition.rows.len();
+        let width = definition.rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        let mut solid = vec![false; width * height];
+
+        for (n, row) in definition.rows.iter().enumerate() {
+            let row_index = height - 1 - n;
+            for (col, tile) in row.chars().enumerate() {
+                solid[row_index * width + col] = definition.legend.get(&tile).map_or(false, |tile| tile.solid);
+            }
+        }
+
+        TileMap {
+            width,
+            height,
+            tile_w: definition.tile_w,
+            tile_h: definition.tile_h,
+            solid,
+            tiles: Vec::new()
+        }
+    }
+
+    /// Loads the map from `path` and puts it into the world as a resource.
+    /// With `draw` the tiles are also created as sprites under everything
+    /// else, which needs the tileset in the `SpriteSheetRegistry`.
+    /// Loading again replaces the tiles of the previous map.
+    pub fn load(world: &mut World, path: &str, draw: bool) {
+        let definition = match TileMapDefinition::load_no_fallback(path) {
+            Ok(definition) => definition,
+            Err(err) => {
+                println!("Failed to load tile map `{}`: {:?}", path, err);
+                return;
+            }
+        };
+
+        let old_tiles = world.res.try_fetch::<TileMap>().map(|old| old.tiles.clone());
+        if let Some(old_tiles) = old_tiles {
+            if let Err(err) = world.delete_entities(&old_tiles) {
+                println!("Failed to remove the old tiles: {:?}", err);
+            }
+        }
+
+        let mut tile_map = TileMap::from_definition(&definition);
+        if draw {
+            tile_map.tiles = create_tiles(world, &definition);
+        }
+        world.add_resource(tile_map);
+    }
+
+    // Tiles outside the map are never solid
+    pub fn is_solid(&self, col: i32, row: i32) -> bool {
+        if col < 0 || row < 0 || col as usize >= self.width || row as usize >= self.height {
+            return false;
+        }
+        self.solid[row as usize * self.width + col as usize]
+    }
+
+    /// Checks if a box at `center` with the given half size touches a solid tile
+    pub fn overlaps_solid(&self, center: Vector3<f32>, half_extents: Vector2<f32>) -> bool {
+        if self.solid.is_empty() {
+            return false;
+        }
+
+        let first_col = ((center.x - half_extents.x) / self.tile_w).floor() as i32;
+        let last_col = ((center.x + half_extents.x) / self.tile_w).floor() as i32;
+        let first_row = ((center.y - half_extents.y) / self.tile_h).floor() as i32;
+        let last_row = ((center.y + half_extents.y) / self.tile_h).floor() as i32;
+
+        (first_row..last_row + 1).any(|row| (first_col..last_col + 1).any(|col| self.is_solid(col, row)))
+    }
+
+    /// Closest position a whole number of tiles away from `center` where
+    /// the box doesn't touch a solid tile, `center` itself if it's free.
+    /// `None` if the map has no room for the box at all.
+    pub fn nearest_free(&self, center: Vector3<f32>, half_extents: Vector2<f32>) -> Option<Vector3<f32>> {
+        if !self.overlaps_solid(center, half_extents) {
+            return Some(center);
+        }
+
+        // Rings of candidates around `center`, one tile further each time
+        let max_radius = self.width.max(self.height) as i32 + 1;
+        for radius in 1..max_radius + 1 {
+            let mut closest: Option<(f32, Vector3<f32>)> = None;
+            for dy in -radius..radius + 1 {
+                for dx in -radius..radius + 1 {
+                    if dx.abs() != radius && dy.abs() != radius {
+                        continue;
+                    }
+
+                    let offset = Vector2::new(dx as f32 * self.tile_w, dy as f32 * self.tile_h);
+                    let candidate = center + offset.extend(0.0);
+                    let distance = offset.x * offset.x + offset.y * offset.y;
+                    if !self.overlaps_solid(candidate, half_extents)
+                        && closest.map_or(true, |(closest_distance, _)| distance < closest_distance)
+                    {
+                        closest = Some((distance, candidate));
+                    }
+                }
+            }
+            if let Some((_, position)) = closest {
+                return Some(position);
+            }
+        }
+        None
+    }
+
+    /// Moves a box by `movement` one axis at a time, stopping it against
+    /// the edge of any solid tile on the way. Movements longer than a tile
+    /// are done in several steps, so a fast entity can't skip over a wall.
+    pub fn move_box(
+        &self,
+        position: Vector3<f32>,
+        movement: Vector2<f32>,
+        half_extents: Vector2<f32>
+    ) -> Vector3<f32> {
+        let max_step = self.tile_w.min(self.tile_h);
+        let steps = if self.solid.is_empty() || max_step <= 0.0 {
+            1
+        } else {
+            (movement.x.abs().max(movement.y.abs()) / max_step).ceil().max(1.0) as usize
+        };
+
+        let step = movement / steps as f32;
+        let mut position = position;
+        for _ in 0..steps {
+            position = self.move_box_step(position, step, half_extents);
+        }
+        position
+    }
+
+    // One step of `move_box`, no longer than a tile
+    fn move_box_step(
+        &self,
+        position: Vector3<f32>,
+        movement: Vector2<f32>,
+        half_extents: Vector2<f32>
+    ) -> Vector3<f32> {
+        let mut position = position;
+
+        position.x += movement.x;
+        if movement.x != 0.0 && self.overlaps_solid(position, half_extents) {
+            position.x = if movement.x > 0.0 {
+                let col = ((position.x + half_extents.x) / self.tile_w).floor();
+                col * self.tile_w - half_extents.x - SKIN
+            } else {
+                let col = ((position.x - half_extents.x) / self.tile_w).floor();
+                (col + 1.0) * self.tile_w + half_extents.x + SKIN
+            };
+        }
+
+        position.y += movement.y;
+        if movement.y != 0.0 && self.overlaps_solid(position, half_extents) {
+            position.y = if movement.y > 0.0 {
+                let row = ((position.y + half_extents.y) / self.tile_h).floor();
+                row * self.tile_h - half_extents.y - SKIN
+            } else {
+                let row = ((position.y - half_extents.y) / self.tile_h).floor();
+                (row + 1.0) * self.tile_h + half_extents.y + SKIN
+            };
+        }
+
+        position
+    }
+
+    /// Moves by `movement`, stopping at solid tiles when the entity has a collider
+    pub fn move_entity(
+        &self,
+        position: Vector3<f32>,
+        movement: Vector2<f32>,
+        collider: Option<&Collider>
+    ) -> Vector3<f32> {
+        match collider {
+            Some(collider) => self.move_box(position, movement, collider.half_extents),
+            None => position + movement.extend(0.0)
+        }
+    }
+}
+
+fn create_tiles(world: &mut World, definition: &TileMapDefinition) -> Vec<Entity> {
+    let (sprite_sheet, sprite_numbers) = match world.read_resource::<SpriteSheetRegistry>().get(&definition.tileset) {
+        Some(sheet) => {
+            let mut sprite_numbers = HashMap::new();
+            for (character, tile) in definition.legend.iter() {
+                match sheet.definition.sprite_number(&tile.sprite) {
+                    Some(number) => {
+                        sprite_numbers.insert(*character, number);
+                    }
+                    None => println!("Tile `{}` uses unknown sprite {} of `{}`", character, tile.sprite, definition.tileset)
+                }
+            }
+            (sheet.handle.clone(), sprite_numbers)
+        }
+        None => {
+            println!("Tile map uses unknown tileset `{}`", definition.tileset);
+            return Vec::new();
+        }
+    };
+
+    let mut tiles = Vec::new();
+
+    let height = definition.rows.len();
+    for (n, row) in definition.rows.iter().enumerate() {
+        let row_index = height - 1 - n;
+        for (col, tile) in row.chars().enumerate() {
+            // Tiles with an unknown sprite still block, they're just not drawn
+            let sprite_number = match sprite_numbers.get(&tile) {
+                Some(sprite_number) => *sprite_number,
+                None => continue
+            };
+
+            // Sprites are centered, and behind everything at z -1.0
+            let mut transform = Transform::default();
+            transform.translation = Vector3::new(
+                (col as f32 + 0.5) * definition.tile_w,
+                (row_index as f32 + 0.5) * definition.tile_h,
+                -1.0
+            );
+
+            let tile = world
+                .create_entity()
+                .with(SpriteRender {
+                    sprite_sheet: sprite_sheet.clone(),
+                    sprite_number,
+                    flip_horizontal: false,
+                    flip_vertical: false
+                })
+                .with(transform)
+                .with(GlobalTransform::default())
+                .build();
+            tiles.push(tile);
+        }
+    }
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One tile wide wall in the middle of a 7 x 3 map of 32 x 32 tiles
+    fn wall_map() -> TileMap {
+        let mut legend = HashMap::new();
+        legend.insert('.', TileDefinition { sprite: SpriteRef::Number(0), solid: false });
+        legend.insert('#', TileDefinition { sprite: SpriteRef::Name("wall".to_string()), solid: true });
+        TileMap::from_definition(&TileMapDefinition {
+            tileset: "tiles".to_string(),
+            tile_w: 32.0,
+            tile_h: 32.0,
+            legend,
+            rows: vec!["...#...".to_string(), "...#...".to_string(), "...#...".to_string()]
+        })
+    }
+
+    #[test]
+    fn long_movement_stops_at_the_wall() {
+        let tile_map = wall_map();
+        let half_extents = Vector2::new(8.0, 8.0);
+        let start = Vector3::new(48.0, 48.0, 0.0);
+
+        // Far enough to land past the wall in one go
+        let moved = tile_map.move_box(start, Vector2::new(120.0, 0.0), half_extents);
+        assert!(moved.x + half_extents.x <= 96.0, "went through the wall to {:?}", moved);
+        assert!(moved.x > 80.0, "stopped too early at {:?}", moved);
+        assert_eq!(moved.y, start.y);
+    }
+
+    #[test]
+    fn short_movement_is_unchanged() {
+        let tile_map = wall_map();
+        let start = Vector3::new(16.0, 16.0, 0.0);
+        let moved = tile_map.move_box(start, Vector2::new(10.0, 5.0), Vector2::new(8.0, 8.0));
+        assert_eq!(moved, Vector3::new(26.0, 21.0, 0.0));
+    }
+
+    #[test]
+    fn box_inside_a_wall_is_moved_next_to_it() {
+        let tile_map = wall_map();
+        let half_extents = Vector2::new(8.0, 8.0);
+
+        let free = Vector3::new(48.0, 48.0, 0.0);
+        assert_eq!(tile_map.nearest_free(free, half_extents), Some(free));
+
+        let inside = Vector3::new(112.0, 48.0, 0.0);
+        let moved = tile_map.nearest_free(inside, half_extents).unwrap();
+        assert!(!tile_map.overlaps_solid(moved, half_extents));
+        assert_eq!((moved.x - inside.x).abs(), 32.0);
+        assert_eq!(moved.y, inside.y);
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Notices changes to files by polling their modification times, cheap
+/// enough to do a couple of times a second for a handful of files
+#[derive(Default, Debug)]
+pub struct FileWatcher {
+    // Modification time seen on the last check, `None` for missing files
+    files: HashMap<PathBuf, Option<SystemTime>>,
+    // Scanned again on every check for files added to them
+    directories: Vec<PathBuf>
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        FileWatcher::default()
+    }
+
+    /// Starts watching `path`, its current state doesn't count as a change
+    pub fn watch<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified(&path);
+        self.files.insert(path, modified);
+    }
+
+    /// Watches every file directly in `directory`, including the ones
+    /// added to it later
+    pub fn watch_dir<P: AsRef<Path>>(&mut self, directory: P) {
+        let directory = directory.as_ref().to_path_buf();
+        match files_in(&directory) {
+            Ok(paths) => for path in paths {
+                self.watch(path);
+            },
+            Err(err) => println!("Failed to watch `{:?}`: {}", directory, err)
+        }
+        self.directories.push(directory);
+    }
+
+    /// Files written or removed since the last check
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        // A new file hasn't been seen before, so it shows up as a change
+        for directory in &self.directories {
+            for path in files_in(directory).unwrap_or_default() {
+                self.files.entry(path).or_insert(None);
+            }
+        }
+
+        let mut changed = Vec::new();
+        for (path, last_modified) in self.files.iter_mut() {
+            let modified = modified(path);
+            if modified != *last_modified {
+                *last_modified = modified;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+fn files_in(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    Ok(fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect())
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn files_added_to_a_watched_directory_are_changes() {
+        let directory = env::temp_dir().join(format!("watcher_test_{}", ::std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("old.ron"), "()").unwrap();
+
+        let mut watcher = FileWatcher::new();
+        watcher.watch_dir(&directory);
+        assert!(watcher.changed().is_empty());
+
+        fs::write(directory.join("new.ron"), "()").unwrap();
+        assert_eq!(watcher.changed(), vec![directory.join("new.ron")]);
+        assert!(watcher.changed().is_empty());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}